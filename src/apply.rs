@@ -0,0 +1,102 @@
+use std::{path::PathBuf, io, sync::{Arc, Mutex}, thread::{self, JoinHandle}};
+use globset::GlobSet;
+use crate::helpers::{self, CopyStats, LinkCache};
+
+/// An enabled mod with its game folder already picked, ready to be copied.
+pub struct ApplyJob {
+    /// Index of the mod in the list that is being applied.
+    pub index: usize,
+    pub name: String,
+    pub scripts: Vec<String>,
+    pub source: PathBuf,
+    pub folder: String,
+    pub destination: PathBuf,
+    pub exclude: GlobSet,
+}
+
+/// The outcome of copying one mod. Nothing is logged on the apply thread, so the caller reports these.
+pub struct CopiedMod {
+    pub index: usize,
+    pub folder: String,
+    pub copy: io::Result<CopyStats>,
+    pub verify: Option<io::Result<Vec<PathBuf>>>,
+    pub scripts: Vec<String>,
+    pub missing_scripts: Vec<String>,
+}
+
+#[derive(Clone, Default)]
+pub struct ApplyProgress {
+    pub copied: u64,
+    pub total: u64,
+    pub current: String,
+}
+
+pub struct ApplyTask {
+    progress: Arc<Mutex<ApplyProgress>>,
+    handle: JoinHandle<Vec<CopiedMod>>,
+}
+
+impl ApplyTask {
+    pub fn start(jobs: Vec<ApplyJob>, hardlink_duplicates: bool, verify: bool) -> ApplyTask {
+        let progress = Arc::new(Mutex::new(ApplyProgress::default()));
+        let thread_progress = progress.clone();
+        let handle = thread::spawn(move || {
+            copy_mods(jobs, hardlink_duplicates, verify, |progress| {
+                *thread_progress.lock().unwrap() = progress;
+            })
+        });
+        ApplyTask { progress, handle }
+    }
+
+    pub fn progress(&self) -> ApplyProgress {
+        self.progress.lock().unwrap().clone()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    pub fn join(self) -> Result<Vec<CopiedMod>, String> {
+        self.handle.join().map_err(|_| "Apply thread panicked!".to_owned())
+    }
+}
+
+pub fn copy_mods(jobs: Vec<ApplyJob>, hardlink_duplicates: bool, verify: bool, mut on_progress: impl FnMut(ApplyProgress)) -> Vec<CopiedMod> {
+    let total: u64 = jobs.iter()
+        .map(|job| helpers::dir_size(&job.source, &job.exclude).unwrap_or_default())
+        .sum();
+    let mut copied: u64 = 0;
+    let mut link_cache = LinkCache::default();
+    let mut results = Vec::new();
+    for job in jobs {
+        let links = match hardlink_duplicates {
+            true => Some(&mut link_cache),
+            false => None,
+        };
+        let copy = helpers::copy_recursively_with_progress(&job.source, &job.destination, &job.exclude, links, |bytes, _| {
+            on_progress(ApplyProgress { copied: (copied + bytes).min(total), total, current: job.name.clone() });
+        });
+        let mut result = CopiedMod {
+            index: job.index,
+            folder: job.folder,
+            copy,
+            verify: None,
+            scripts: Vec::new(),
+            missing_scripts: Vec::new(),
+        };
+        if let Ok(stats) = &result.copy {
+            copied += stats.bytes + stats.linked_bytes;
+            if verify {
+                result.verify = Some(helpers::verify_copy(&job.source, &job.destination, &job.exclude));
+            }
+            for script in job.scripts {
+                match helpers::has_script_package(&job.destination, &script) {
+                    true => result.scripts.push(script),
+                    false => result.missing_scripts.push(script),
+                }
+            }
+        }
+        results.push(result);
+    }
+    results
+}
@@ -0,0 +1,156 @@
+use std::{path::{Path, PathBuf}, fs, io::{self, Cursor}, ffi::OsStr};
+use tempfile::Builder;
+use crate::helpers::{self, SplitArchive, SplitKind};
+
+pub enum ArchiveError {
+    Io(io::Error),
+    Corrupt(String),
+    UnsupportedCompression(String),
+    Encrypted,
+    Extract(String),
+    UnsupportedExtension(String),
+    MissingParts(String, Vec<usize>),
+    MissingFirstPart(String),
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveError::Io(e) => write!(f, "{}", e),
+            ArchiveError::Corrupt(e) => write!(f, "The archive is corrupt or incomplete. Try downloading it again. ({})", e),
+            ArchiveError::UnsupportedCompression(e) => write!(f, "The archive uses an unsupported compression method. Try repacking it as a regular zip. ({})", e),
+            ArchiveError::Encrypted => write!(f, "The archive is encrypted and needs a password. Extract it manually and install the folder instead."),
+            ArchiveError::Extract(e) => write!(f, "{}", e),
+            ArchiveError::UnsupportedExtension(extension) => write!(f, "Invalid file extension {}!", extension),
+            ArchiveError::MissingParts(name, missing) => {
+                let missing: Vec<String> = missing.iter().map(|number| number.to_string()).collect();
+                write!(f, "Split archive {} is missing part(s) {}! Place all parts in the same folder and try again.", name, missing.join(", "))
+            }
+            ArchiveError::MissingFirstPart(name) => write!(f, "Could not find the first part of split archive {}!", name),
+        }
+    }
+}
+
+impl From<io::Error> for ArchiveError {
+    fn from(e: io::Error) -> ArchiveError {
+        ArchiveError::Io(e)
+    }
+}
+
+impl From<zip_extract::ZipExtractError> for ArchiveError {
+    fn from(e: zip_extract::ZipExtractError) -> ArchiveError {
+        match e {
+            zip_extract::ZipExtractError::Io(e) => ArchiveError::Io(e),
+            zip_extract::ZipExtractError::Zip(zip_extract::ZipError::Io(e)) => ArchiveError::Io(e),
+            zip_extract::ZipExtractError::Zip(zip_extract::ZipError::InvalidArchive(e)) => ArchiveError::Corrupt(e.to_owned()),
+            zip_extract::ZipExtractError::Zip(zip_extract::ZipError::UnsupportedArchive(e)) if e.to_lowercase().contains("password") => ArchiveError::Encrypted,
+            zip_extract::ZipExtractError::Zip(zip_extract::ZipError::UnsupportedArchive(e)) => ArchiveError::UnsupportedCompression(e.to_owned()),
+            e => ArchiveError::Extract(e.to_string()),
+        }
+    }
+}
+
+impl From<sevenz_rust::Error> for ArchiveError {
+    fn from(e: sevenz_rust::Error) -> ArchiveError {
+        match e {
+            sevenz_rust::Error::PasswordRequired => ArchiveError::Encrypted,
+            sevenz_rust::Error::UnsupportedCompressionMethod(method) => ArchiveError::UnsupportedCompression(method),
+            sevenz_rust::Error::BadSignature(_)
+            | sevenz_rust::Error::ChecksumVerificationFailed
+            | sevenz_rust::Error::NextHeaderCrcMismatch => ArchiveError::Corrupt(e.to_string()),
+            e => ArchiveError::Extract(e.to_string()),
+        }
+    }
+}
+
+impl<T> From<unrar::UnrarError<T>> for ArchiveError {
+    fn from(e: unrar::UnrarError<T>) -> ArchiveError {
+        match e.code {
+            unrar::error::Code::MissingPassword | unrar::error::Code::BadPassword => ArchiveError::Encrypted,
+            unrar::error::Code::BadData | unrar::error::Code::BadArchive | unrar::error::Code::EndArchive => ArchiveError::Corrupt(e.to_string()),
+            unrar::error::Code::UnknownFormat => ArchiveError::UnsupportedCompression(e.to_string()),
+            _ => ArchiveError::Extract(e.to_string()),
+        }
+    }
+}
+
+/// File name, without extension, of the archive kept in a mod folder when the mod is stored compressed.
+pub const PACKED_NAME: &str = "Packed";
+
+/// Returns the archive of a mod stored compressed, e.g. `Mods/MyMod/Packed.7z`.
+pub fn packed_archive(folder: &Path) -> Option<PathBuf> {
+    ["zip", "7z", "rar"].iter()
+        .map(|extension| folder.join(format!("{}.{}", PACKED_NAME, extension)))
+        .find(|path| path.is_file())
+}
+
+/// Extracts a zip, 7z or rar archive (including split 7z and rar volumes) into `destination`.
+/// If the archive only contains a single folder, its contents are extracted without it.
+/// The archive is staged in `temp_dir` when one is given, otherwise next to `destination`.
+pub fn extract(path: &Path, destination: &Path, temp_dir: Option<&Path>) -> Result<(), ArchiveError> {
+    let destination = &helpers::long_path(destination);
+    let parent = destination.parent().unwrap_or(destination);
+    fs::create_dir_all(parent)?;
+    let staging = Builder::new().prefix(".xrdmodman").tempdir_in(temp_dir.unwrap_or(parent))?;
+    match helpers::split_archive(path) {
+        Some(split) => extract_split(split, staging.path())?,
+        None => extract_single(path, staging.path())?,
+    }
+    helpers::copy_recursively(strip_wrapper(staging.path())?, destination)?;
+    Ok(())
+}
+
+fn extract_split(split: SplitArchive, destination: &Path) -> Result<(), ArchiveError> {
+    if !split.missing.is_empty() {
+        return Err(ArchiveError::MissingParts(split.base_name, split.missing))
+    }
+    let first = split.first().cloned().ok_or_else(|| ArchiveError::MissingFirstPart(split.base_name.clone()))?;
+    match split.kind {
+        SplitKind::Rar => {
+            let archive = unrar::Archive::new(&first).map_err(|e| ArchiveError::Extract(e.to_string()))?;
+            let mut archive = archive.extract_to(destination).map_err(|e| missing_volume(e, &split))?;
+            archive.process().map_err(|e| missing_volume(e, &split))?;
+            Ok(())
+        }
+        SplitKind::SevenZip => {
+            let joined_dir = Builder::new().prefix(".xrdmodman").tempdir_in(destination.parent().unwrap_or(destination))?;
+            let joined = joined_dir.path().join(format!("{}.7z", split.base_name));
+            helpers::join_files(&split.parts, &joined)?;
+            extract_single(&joined, destination)
+        }
+    }
+}
+
+/// unrar only notices a missing volume when it fails to open it, which for the last volume is the
+/// only sign that it is missing. All volumes before it were found, so it is the one after them.
+fn missing_volume<T>(e: unrar::UnrarError<T>, split: &SplitArchive) -> ArchiveError {
+    match e.code {
+        unrar::error::Code::EOpen => ArchiveError::MissingParts(split.base_name.clone(), vec![split.parts.len() + 1]),
+        _ => e.into(),
+    }
+}
+
+fn extract_single(path: &Path, destination: &Path) -> Result<(), ArchiveError> {
+    match path.extension().and_then(OsStr::to_str).map(|extension| extension.to_lowercase()).as_deref() {
+        Some("zip") => {
+            let bytes = fs::read(path)?;
+            Ok(zip_extract::extract(Cursor::new(bytes), destination, false)?)
+        }
+        Some("7z") => Ok(sevenz_rust::decompress_file(path, destination)?),
+        Some("rar") => {
+            let archive = unrar::Archive::new(path).map_err(|e| ArchiveError::Extract(e.to_string()))?;
+            let mut archive = archive.extract_to(destination)?;
+            archive.process()?;
+            Ok(())
+        }
+        extension => Err(ArchiveError::UnsupportedExtension(extension.unwrap_or_default().to_owned())),
+    }
+}
+
+fn strip_wrapper(root: &Path) -> io::Result<PathBuf> {
+    let entries: Vec<fs::DirEntry> = fs::read_dir(root)?.collect::<io::Result<_>>()?;
+    match entries.as_slice() {
+        [entry] if entry.file_type()?.is_dir() => Ok(entry.path()),
+        _ => Ok(root.to_path_buf()),
+    }
+}
@@ -0,0 +1,105 @@
+use std::{path::{Component, Path}, fs::{self, File}, io::{self, Read}};
+use ini::Ini;
+use globset::GlobSet;
+use zip::{ZipArchive, ZipWriter, CompressionMethod, write::FileOptions, result::ZipResult};
+use crate::helpers;
+
+pub struct BackupEntry {
+    pub folder: String,
+    pub name: String,
+    pub version: String,
+}
+
+/// Writes every file in the mods folder to a deflate-compressed zip. Returns the number of files written.
+pub fn create_backup(mods_path: &Path, destination: &Path) -> ZipResult<usize> {
+    zip_folder(mods_path, destination, None, &GlobSet::empty())
+}
+
+/// Streams the files in `source` into a deflate-compressed zip, keeping their relative paths (under
+/// `root` if given) and skipping anything matched by `exclude`. Returns the number of files written.
+pub fn zip_folder(source: &Path, destination: &Path, root: Option<&str>, exclude: &GlobSet) -> ZipResult<usize> {
+    let mut zip = ZipWriter::new(File::create(destination)?);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut written = 0;
+    for file in helpers::list_files(source)? {
+        if file.ancestors().any(|path| exclude.is_match(path)) {
+            continue
+        }
+        let name: Vec<String> = root.map(|root| root.to_owned()).into_iter()
+            .chain(file.components().map(|component| component.as_os_str().to_string_lossy().into_owned()))
+            .collect();
+        zip.start_file(name.join("/"), options)?;
+        io::copy(&mut File::open(source.join(&file))?, &mut zip)?;
+        written += 1;
+    }
+    zip.finish()?;
+    Ok(written)
+}
+
+fn top_folder(path: &Path) -> Option<String> {
+    match path.components().next()? {
+        Component::Normal(folder) => Some(folder.to_string_lossy().into_owned()),
+        _ => None,
+    }
+}
+
+/// Lists the mod folders in a backup along with the name and version from each folder's mod.ini,
+/// without extracting anything.
+pub fn list_backup(path: &Path) -> ZipResult<Vec<BackupEntry>> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let mut entries: Vec<BackupEntry> = Vec::new();
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        let enclosed = match file.enclosed_name() {
+            Some(enclosed) => enclosed.to_path_buf(),
+            None => continue,
+        };
+        let folder = match top_folder(&enclosed) {
+            Some(folder) if enclosed.components().count() > 1 => folder,
+            _ => continue,
+        };
+        if !entries.iter().any(|entry| entry.folder == folder) {
+            entries.push(BackupEntry { folder: folder.clone(), name: folder.clone(), version: "".to_owned() });
+        }
+        if enclosed != Path::new(&folder).join("mod.ini") {
+            continue
+        }
+        let mut text = String::new();
+        file.read_to_string(&mut text)?;
+        if let Some(description) = Ini::load_from_str_noescape(&text).ok().and_then(|ini| ini.section(Some("Description")).cloned()) {
+            let entry = entries.iter_mut().find(|entry| entry.folder == folder).unwrap();
+            entry.name = description.get("Name").filter(|name| !name.is_empty()).unwrap_or(&folder).to_owned();
+            entry.version = description.get("Version").unwrap_or_default().to_owned();
+        }
+    }
+    entries.sort_by(|a, b| a.folder.to_lowercase().cmp(&b.folder.to_lowercase()));
+    Ok(entries)
+}
+
+/// Extracts only the given mod folders from a backup into the mods folder. Returns the number of files written.
+pub fn restore_backup(path: &Path, mods_path: &Path, folders: &[String]) -> ZipResult<usize> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let mut restored = 0;
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        let enclosed = match file.enclosed_name() {
+            Some(enclosed) => enclosed.to_path_buf(),
+            None => continue,
+        };
+        match top_folder(&enclosed) {
+            Some(folder) if folders.contains(&folder) => (),
+            _ => continue,
+        }
+        let destination = mods_path.join(&enclosed);
+        if file.is_dir() {
+            fs::create_dir_all(&destination)?;
+            continue
+        }
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut file, &mut File::create(&destination)?)?;
+        restored += 1;
+    }
+    Ok(restored)
+}
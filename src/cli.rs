@@ -0,0 +1,61 @@
+const PROTOCOL_PREFIX: &str = "xrdmodman:";
+
+/// A one-click install link, as passed by the browser: `xrdmodman:<download url>[,<item type>,<item id>]`.
+pub struct OneClickRequest {
+    pub url: String,
+    pub item_type: Option<String>,
+    pub item_id: Option<String>,
+}
+
+impl OneClickRequest {
+    pub fn parse(link: &str) -> Result<OneClickRequest, String> {
+        let link = link.trim();
+        let payload = match link.get(..PROTOCOL_PREFIX.len()) {
+            Some(prefix) if prefix.eq_ignore_ascii_case(PROTOCOL_PREFIX) => &link[PROTOCOL_PREFIX.len()..],
+            _ => return Err(format!("Invalid one-click link {}! It must start with {}", link, PROTOCOL_PREFIX)),
+        };
+        let mut parts = payload.split(',').map(|part| part.trim());
+        let url = parts.next().unwrap_or_default();
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(format!("Invalid one-click link {}! It does not contain a download URL.", link))
+        }
+        let mut optional = || parts.next().filter(|part| !part.is_empty()).map(|part| part.to_owned());
+        Ok(OneClickRequest {
+            url: url.to_owned(),
+            item_type: optional(),
+            item_id: optional(),
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct CliArgs {
+    pub quiet: bool,
+    pub no_update: bool,
+    pub download: Option<OneClickRequest>,
+    pub enable: Vec<String>,
+    pub disable: Vec<String>,
+    pub launch: bool,
+}
+
+impl CliArgs {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<CliArgs, String> {
+        let mut cli = CliArgs::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-quiet" => cli.quiet = true,
+                "-no-update" => cli.no_update = true,
+                "-launch" => cli.launch = true,
+                "-download" => cli.download = Some(OneClickRequest::parse(&args.next().ok_or("Missing link after -download!")?)?),
+                "-enable" => cli.enable.push(args.next().ok_or("Missing mod name after -enable!")?),
+                "-disable" => cli.disable.push(args.next().ok_or("Missing mod name after -disable!")?),
+                _ => return Err(format!("Unknown argument {}!", arg)),
+            }
+        }
+        Ok(cli)
+    }
+
+    pub fn has_commands(&self) -> bool {
+        !self.enable.is_empty() || !self.disable.is_empty() || self.launch
+    }
+}
@@ -0,0 +1,582 @@
+use std::path::Path;
+use ini::Ini;
+use crate::helpers;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Theme {
+    Light,
+    #[default]
+    Dark,
+    System,
+}
+
+impl Theme {
+    fn parse(value: &str) -> Theme {
+        match value {
+            "Light" => Theme::Light,
+            "System" => Theme::System,
+            _ => Theme::Dark,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+            Theme::System => "System",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum LaunchMethod {
+    #[default]
+    Steam,
+    Direct,
+}
+
+impl LaunchMethod {
+    fn parse(value: &str) -> LaunchMethod {
+        match value {
+            "Direct" => LaunchMethod::Direct,
+            _ => LaunchMethod::Steam,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            LaunchMethod::Steam => "Steam",
+            LaunchMethod::Direct => "Direct",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum CollisionPolicy {
+    #[default]
+    Overwrite,
+    Rename,
+    Skip,
+}
+
+impl CollisionPolicy {
+    fn parse(value: &str) -> CollisionPolicy {
+        match value {
+            "Rename" => CollisionPolicy::Rename,
+            "Skip" => CollisionPolicy::Skip,
+            _ => CollisionPolicy::Overwrite,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            CollisionPolicy::Overwrite => "Overwrite",
+            CollisionPolicy::Rename => "Rename",
+            CollisionPolicy::Skip => "Skip",
+        }
+    }
+}
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// A yearly range of dates in which a mod is enabled, stored as `MM-DD..MM-DD`.
+/// Ranges that end before they start wrap around the new year, e.g. `12-01..01-06`.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Schedule {
+    pub start: (u32, u32),
+    pub end: (u32, u32),
+}
+
+impl Schedule {
+    fn parse(value: &str) -> Option<Schedule> {
+        let parse_date = |date: &str| -> Option<(u32, u32)> {
+            let (month, day) = date.trim().split_once('-')?;
+            let (month, day) = (month.parse::<u32>().ok()?, day.parse::<u32>().ok()?);
+            match (1..=12).contains(&month) && (1..=31).contains(&day) {
+                true => Some((month, day)),
+                false => None,
+            }
+        };
+        let (start, end) = value.split_once("..")?;
+        Some(Schedule { start: parse_date(start)?, end: parse_date(end)? })
+    }
+
+    fn as_string(&self) -> String {
+        format!("{:02}-{:02}..{:02}-{:02}", self.start.0, self.start.1, self.end.0, self.end.1)
+    }
+
+    pub fn contains(&self, month: u32, day: u32) -> bool {
+        let date = (month, day);
+        match self.start <= self.end {
+            true => self.start <= date && date <= self.end,
+            false => date >= self.start || date <= self.end,
+        }
+    }
+}
+
+impl std::fmt::Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} – {} {}", MONTHS[self.start.0 as usize - 1], self.start.1, MONTHS[self.end.0 as usize - 1], self.end.1)
+    }
+}
+
+/// How often a mod was included when applying mods, and when it was last included.
+#[derive(Clone, Copy, PartialEq)]
+pub struct ApplyStats {
+    pub count: u32,
+    pub last_applied: i64,
+}
+
+impl ApplyStats {
+    fn parse(value: &str) -> Option<ApplyStats> {
+        let (count, last_applied) = value.split_once(',')?;
+        Some(ApplyStats { count: count.trim().parse().ok()?, last_applied: last_applied.trim().parse().ok()? })
+    }
+}
+
+#[derive(Clone)]
+pub struct GeneralConfig {
+    pub new_mods_enabled: bool,
+    pub auto_update: bool,
+    pub close_to_tray: bool,
+    pub json_log: bool,
+    pub verify_apply: bool,
+    pub hardlink_duplicates: bool,
+    pub write_mod_json: bool,
+    pub preserve_unmanaged_mods: bool,
+    pub compressed_storage: bool,
+    pub register_protocol: bool,
+    pub block_incompatible_mods: bool,
+    /// Per-mod settings are keyed by mod folder. Older versions used the Name from mod.ini instead.
+    pub mods_keyed_by_folder: bool,
+    pub theme: Theme,
+    pub launch_method: LaunchMethod,
+    pub on_name_collision: CollisionPolicy,
+    pub app_id: Option<String>,
+    pub launch_options: String,
+    pub download_threads: Option<String>,
+    pub folder_template: Option<String>,
+    pub max_download_bytes_per_sec: Option<String>,
+    pub max_enabled_mods: Option<String>,
+    pub auto_refresh_seconds: Option<String>,
+    pub sandbox_path: Option<String>,
+    pub mods_path: Option<String>,
+    pub temp_dir: Option<String>,
+    pub last_installed_version: Option<String>,
+    pub other: Vec<(String, String)>,
+}
+
+impl Default for GeneralConfig {
+    fn default() -> GeneralConfig {
+        GeneralConfig {
+            new_mods_enabled: false,
+            auto_update: false,
+            close_to_tray: false,
+            json_log: false,
+            verify_apply: false,
+            hardlink_duplicates: false,
+            write_mod_json: false,
+            preserve_unmanaged_mods: false,
+            compressed_storage: false,
+            register_protocol: true,
+            block_incompatible_mods: true,
+            mods_keyed_by_folder: false,
+            theme: Theme::default(),
+            launch_method: LaunchMethod::default(),
+            on_name_collision: CollisionPolicy::default(),
+            app_id: None,
+            launch_options: String::new(),
+            download_threads: None,
+            folder_template: None,
+            max_download_bytes_per_sec: None,
+            max_enabled_mods: None,
+            auto_refresh_seconds: None,
+            sandbox_path: None,
+            mods_path: None,
+            temp_dir: None,
+            last_installed_version: None,
+            other: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WindowConfig {
+    pub console_visible: bool,
+    pub console_height: Option<f32>,
+    pub ui_scale: Option<f32>,
+    pub high_contrast: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> WindowConfig {
+        WindowConfig {
+            console_visible: true,
+            console_height: None,
+            ui_scale: None,
+            high_contrast: false,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ConsoleConfig {
+    pub info_color: Option<String>,
+    pub warn_color: Option<String>,
+    pub error_color: Option<String>,
+}
+
+#[derive(Clone, Default)]
+pub struct Config {
+    pub general: GeneralConfig,
+    pub window: WindowConfig,
+    pub console: ConsoleConfig,
+    pub mods: Vec<(String, bool)>,
+    pub collapsed_groups: Vec<String>,
+    pub notes: Vec<(String, String)>,
+    pub bulk_excluded: Vec<String>,
+    pub known_good: Vec<String>,
+    pub installed_at: Vec<(String, i64)>,
+    pub schedules: Vec<(String, Schedule)>,
+    pub apply_stats: Vec<(String, ApplyStats)>,
+    /// Sections this version does not know about, kept so they survive a save.
+    pub other_sections: Vec<(String, Vec<(String, String)>)>,
+}
+
+/// Sections read into the typed fields of `Config`. Any other section goes to `other_sections`.
+const KNOWN_SECTIONS: [&str; 11] = ["General", "Window", "Console", "Mods", "CollapsedGroups", "BulkExcluded", "KnownGood", "InstalledAt", "Schedules", "ApplyStats", "Notes"];
+
+fn bool_str(value: bool) -> &'static str {
+    match value {
+        true => "True",
+        false => "False",
+    }
+}
+
+impl Config {
+    pub fn new() -> Config {
+        Config::default()
+    }
+
+    /// Reads config.ini with unescaping, since `save` escapes values such as the backslashes in ModsPath.
+    pub fn load(path: impl AsRef<Path>) -> Result<Config, ini::Error> {
+        Ok(Config::from_ini(&Ini::load_from_file(path)?))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        helpers::write_ini_atomic(&self.to_ini(), path)
+    }
+
+    pub fn from_ini(ini: &Ini) -> Config {
+        let mut config = Config::new();
+        if let Some(section) = ini.section(Some("General")) {
+            for (key, value) in section.iter() {
+                let text = Some(value.to_owned()).filter(|value| !value.is_empty());
+                match key {
+                    "NewModsEnabled" => config.general.new_mods_enabled = value == "True",
+                    "AutoUpdate" => config.general.auto_update = value == "True",
+                    "CloseToTray" => config.general.close_to_tray = value == "True",
+                    "JsonLog" => config.general.json_log = value == "True",
+                    "VerifyApply" => config.general.verify_apply = value == "True",
+                    "HardlinkDuplicates" => config.general.hardlink_duplicates = value == "True",
+                    "WriteModJson" => config.general.write_mod_json = value == "True",
+                    "PreserveUnmanagedMods" => config.general.preserve_unmanaged_mods = value == "True",
+                    "CompressedStorage" => config.general.compressed_storage = value == "True",
+                    "RegisterProtocol" => config.general.register_protocol = value != "False",
+                    "BlockIncompatibleMods" => config.general.block_incompatible_mods = value != "False",
+                    "ModsKeyedByFolder" => config.general.mods_keyed_by_folder = value == "True",
+                    "Theme" => config.general.theme = Theme::parse(value),
+                    "LaunchMethod" => config.general.launch_method = LaunchMethod::parse(value),
+                    "OnNameCollision" => config.general.on_name_collision = CollisionPolicy::parse(value),
+                    "AppId" => config.general.app_id = text,
+                    "LaunchOptions" => config.general.launch_options = value.to_owned(),
+                    "DownloadThreads" => config.general.download_threads = text,
+                    // Older versions named this setting after extraction, which it never affected.
+                    "MaxExtractThreads" => if config.general.download_threads.is_none() {
+                        config.general.download_threads = text;
+                    },
+                    "FolderTemplate" => config.general.folder_template = text,
+                    "MaxDownloadBytesPerSec" => config.general.max_download_bytes_per_sec = text,
+                    "MaxEnabledMods" => config.general.max_enabled_mods = text,
+                    "AutoRefreshSeconds" => config.general.auto_refresh_seconds = text,
+                    "SandboxPath" => config.general.sandbox_path = text,
+                    "ModsPath" => config.general.mods_path = text,
+                    "TempDir" => config.general.temp_dir = text,
+                    "LastInstalledVersion" => config.general.last_installed_version = text,
+                    _ => config.general.other.push((key.to_owned(), value.to_owned())),
+                }
+            }
+        }
+        if let Some(section) = ini.section(Some("Window")) {
+            if let Some(visible) = section.get("ConsoleVisible") {
+                config.window.console_visible = visible != "False";
+            }
+            config.window.console_height = section.get("ConsoleHeight").and_then(|height| height.parse::<f32>().ok());
+            config.window.ui_scale = section.get("UiScale").and_then(|scale| scale.parse::<f32>().ok());
+            config.window.high_contrast = section.get("HighContrast") == Some("True");
+        }
+        if let Some(section) = ini.section(Some("Console")) {
+            let color = |key: &str| section.get(key).map(|value| value.trim().to_owned()).filter(|value| !value.is_empty());
+            config.console.info_color = color("Info");
+            config.console.warn_color = color("Warn");
+            config.console.error_color = color("Error");
+        }
+        if let Some(section) = ini.section(Some("Mods")) {
+            for (name, enabled) in section.iter() {
+                config.set_mod(name, enabled == "True");
+            }
+        }
+        if let Some(section) = ini.section(Some("CollapsedGroups")) {
+            config.collapsed_groups = section.iter().map(|(name, _)| name.to_owned()).collect();
+        }
+        if let Some(section) = ini.section(Some("BulkExcluded")) {
+            config.bulk_excluded = section.iter().map(|(name, _)| name.to_owned()).collect();
+        }
+        if let Some(section) = ini.section(Some("KnownGood")) {
+            config.known_good = section.iter().map(|(name, _)| name.to_owned()).collect();
+        }
+        if let Some(section) = ini.section(Some("InstalledAt")) {
+            config.installed_at = section.iter()
+                .filter_map(|(name, timestamp)| timestamp.parse::<i64>().ok().map(|timestamp| (name.to_owned(), timestamp)))
+                .collect();
+        }
+        if let Some(section) = ini.section(Some("Schedules")) {
+            config.schedules = section.iter()
+                .filter_map(|(name, schedule)| Schedule::parse(schedule).map(|schedule| (name.to_owned(), schedule)))
+                .collect();
+        }
+        if let Some(section) = ini.section(Some("ApplyStats")) {
+            config.apply_stats = section.iter()
+                .filter_map(|(name, stats)| ApplyStats::parse(stats).map(|stats| (name.to_owned(), stats)))
+                .collect();
+        }
+        if let Some(section) = ini.section(Some("Notes")) {
+            config.notes = section.iter().map(|(name, note)| (name.to_owned(), note.to_owned())).collect();
+        }
+        for (name, section) in ini.iter() {
+            if let Some(name) = name.filter(|name| !KNOWN_SECTIONS.contains(name)) {
+                let entries = section.iter().map(|(key, value)| (key.to_owned(), value.to_owned())).collect();
+                config.other_sections.push((name.to_owned(), entries));
+            }
+        }
+        config
+    }
+
+    pub fn to_ini(&self) -> Ini {
+        let mut ini = Ini::new();
+        let general = &self.general;
+        let mut entries: Vec<(&str, &str)> = general.other.iter().map(|(key, value)| (key.as_str(), value.as_str())).collect();
+        entries.push(("NewModsEnabled", bool_str(general.new_mods_enabled)));
+        entries.push(("AutoUpdate", bool_str(general.auto_update)));
+        entries.push(("CloseToTray", bool_str(general.close_to_tray)));
+        entries.push(("JsonLog", bool_str(general.json_log)));
+        entries.push(("VerifyApply", bool_str(general.verify_apply)));
+        entries.push(("HardlinkDuplicates", bool_str(general.hardlink_duplicates)));
+        entries.push(("WriteModJson", bool_str(general.write_mod_json)));
+        entries.push(("PreserveUnmanagedMods", bool_str(general.preserve_unmanaged_mods)));
+        entries.push(("CompressedStorage", bool_str(general.compressed_storage)));
+        entries.push(("RegisterProtocol", bool_str(general.register_protocol)));
+        entries.push(("BlockIncompatibleMods", bool_str(general.block_incompatible_mods)));
+        entries.push(("ModsKeyedByFolder", bool_str(general.mods_keyed_by_folder)));
+        entries.push(("Theme", general.theme.as_str()));
+        entries.push(("LaunchMethod", general.launch_method.as_str()));
+        entries.push(("OnNameCollision", general.on_name_collision.as_str()));
+        let optional = [
+            ("AppId", &general.app_id),
+            ("DownloadThreads", &general.download_threads),
+            ("FolderTemplate", &general.folder_template),
+            ("MaxDownloadBytesPerSec", &general.max_download_bytes_per_sec),
+            ("MaxEnabledMods", &general.max_enabled_mods),
+            ("AutoRefreshSeconds", &general.auto_refresh_seconds),
+            ("SandboxPath", &general.sandbox_path),
+            ("ModsPath", &general.mods_path),
+            ("TempDir", &general.temp_dir),
+            ("LastInstalledVersion", &general.last_installed_version),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                entries.push((key, value));
+            }
+        }
+        if !general.launch_options.is_empty() {
+            entries.push(("LaunchOptions", &general.launch_options));
+        }
+        for (key, value) in entries {
+            ini.with_section(Some("General")).set(key, value);
+        }
+        ini.with_section(Some("Window")).set("ConsoleVisible", bool_str(self.window.console_visible));
+        if let Some(height) = self.window.console_height {
+            ini.with_section(Some("Window")).set("ConsoleHeight", format!("{:.0}", height));
+        }
+        if let Some(scale) = self.window.ui_scale {
+            ini.with_section(Some("Window")).set("UiScale", format!("{:.2}", scale));
+        }
+        ini.with_section(Some("Window")).set("HighContrast", bool_str(self.window.high_contrast));
+        let colors = [
+            ("Info", &self.console.info_color),
+            ("Warn", &self.console.warn_color),
+            ("Error", &self.console.error_color),
+        ];
+        for (key, value) in colors {
+            if let Some(value) = value {
+                ini.with_section(Some("Console")).set(key, value);
+            }
+        }
+        for (name, enabled) in &self.mods {
+            ini.with_section(Some("Mods")).set(name, bool_str(*enabled));
+        }
+        for category in &self.collapsed_groups {
+            ini.with_section(Some("CollapsedGroups")).set(category, "True");
+        }
+        for name in &self.bulk_excluded {
+            ini.with_section(Some("BulkExcluded")).set(name, "True");
+        }
+        for name in &self.known_good {
+            ini.with_section(Some("KnownGood")).set(name, "True");
+        }
+        for (name, timestamp) in &self.installed_at {
+            ini.with_section(Some("InstalledAt")).set(name, timestamp.to_string());
+        }
+        for (name, schedule) in &self.schedules {
+            ini.with_section(Some("Schedules")).set(name, schedule.as_string());
+        }
+        for (name, stats) in &self.apply_stats {
+            ini.with_section(Some("ApplyStats")).set(name, format!("{},{}", stats.count, stats.last_applied));
+        }
+        for (name, note) in &self.notes {
+            ini.with_section(Some("Notes")).set(name, note);
+        }
+        for (name, entries) in &self.other_sections {
+            for (key, value) in entries {
+                ini.with_section(Some(name.as_str())).set(key, value);
+            }
+        }
+        ini
+    }
+
+    pub fn mod_enabled(&self, name: &str) -> Option<bool> {
+        self.mods.iter().find(|(mod_name, _)| mod_name == name).map(|(_, enabled)| *enabled)
+    }
+
+    pub fn set_mod(&mut self, name: &str, enabled: bool) {
+        match self.mods.iter_mut().find(|(mod_name, _)| mod_name == name) {
+            Some(entry) => entry.1 = enabled,
+            None => self.mods.push((name.to_owned(), enabled)),
+        }
+    }
+
+    pub fn rename_mod(&mut self, old_name: &str, new_name: &str) {
+        if let Some(entry) = self.mods.iter_mut().find(|(mod_name, _)| mod_name == old_name) {
+            entry.0 = new_name.to_owned();
+        }
+        for list in [&mut self.bulk_excluded, &mut self.known_good] {
+            for name in list.iter_mut().filter(|name| name.as_str() == old_name) {
+                *name = new_name.to_owned();
+            }
+        }
+        for entry in self.installed_at.iter_mut().filter(|(name, _)| name == old_name) {
+            entry.0 = new_name.to_owned();
+        }
+        for entry in self.schedules.iter_mut().filter(|(name, _)| name == old_name) {
+            entry.0 = new_name.to_owned();
+        }
+        for entry in self.apply_stats.iter_mut().filter(|(name, _)| name == old_name) {
+            entry.0 = new_name.to_owned();
+        }
+        self.rename_note(old_name, new_name);
+    }
+
+    pub fn remove_mod(&mut self, name: &str) {
+        self.mods.retain(|(mod_name, _)| mod_name != name);
+    }
+
+    pub fn set_group_collapsed(&mut self, category: &str, collapsed: bool) {
+        self.collapsed_groups.retain(|name| name != category);
+        if collapsed {
+            self.collapsed_groups.push(category.to_owned());
+        }
+    }
+
+    pub fn set_bulk_excluded(&mut self, name: &str, excluded: bool) {
+        self.bulk_excluded.retain(|mod_name| mod_name != name);
+        if excluded {
+            self.bulk_excluded.push(name.to_owned());
+        }
+    }
+
+    pub fn set_known_good(&mut self, name: &str, known_good: bool) {
+        self.known_good.retain(|mod_name| mod_name != name);
+        if known_good {
+            self.known_good.push(name.to_owned());
+        }
+    }
+
+    pub fn installed_at(&self, name: &str) -> Option<i64> {
+        self.installed_at.iter().find(|(mod_name, _)| mod_name == name).map(|(_, timestamp)| *timestamp)
+    }
+
+    pub fn set_installed_at(&mut self, name: &str, timestamp: i64) {
+        self.installed_at.retain(|(mod_name, _)| mod_name != name);
+        self.installed_at.push((name.to_owned(), timestamp));
+    }
+
+    pub fn schedule(&self, name: &str) -> Option<Schedule> {
+        self.schedules.iter().find(|(mod_name, _)| mod_name == name).map(|(_, schedule)| *schedule)
+    }
+
+    pub fn set_schedule(&mut self, name: &str, schedule: Option<Schedule>) {
+        self.schedules.retain(|(mod_name, _)| mod_name != name);
+        if let Some(schedule) = schedule {
+            self.schedules.push((name.to_owned(), schedule));
+        }
+    }
+
+    pub fn apply_stats(&self, name: &str) -> Option<ApplyStats> {
+        self.apply_stats.iter().find(|(mod_name, _)| mod_name == name).map(|(_, stats)| *stats)
+    }
+
+    pub fn record_apply(&mut self, name: &str, timestamp: i64) {
+        match self.apply_stats.iter_mut().find(|(mod_name, _)| mod_name == name) {
+            Some((_, stats)) => {
+                stats.count += 1;
+                stats.last_applied = timestamp;
+            }
+            None => self.apply_stats.push((name.to_owned(), ApplyStats { count: 1, last_applied: timestamp })),
+        }
+    }
+
+    pub fn clear_apply_stats(&mut self, name: &str) {
+        self.apply_stats.retain(|(mod_name, _)| mod_name != name);
+    }
+
+    pub fn note(&self, name: &str) -> &str {
+        self.notes.iter().find(|(mod_name, _)| mod_name == name).map(|(_, note)| note.as_str()).unwrap_or_default()
+    }
+
+    pub fn set_note(&mut self, name: &str, note: &str) {
+        self.notes.retain(|(mod_name, _)| mod_name != name);
+        if !note.trim().is_empty() {
+            self.notes.push((name.to_owned(), note.to_owned()));
+        }
+    }
+
+    pub fn rename_note(&mut self, old_name: &str, new_name: &str) {
+        let note = self.note(old_name).to_owned();
+        self.set_note(old_name, "");
+        self.set_note(new_name, &note);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_settings_survive_a_save() {
+        let ini = Ini::load_from_str("[General]\nNewModsEnabled=True\nFutureKey=1\n\n[Mods]\nSol=True\n\n[FutureSection]\nKey=Value\n").unwrap();
+        let saved = Config::from_ini(&ini).to_ini();
+        assert_eq!(saved.get_from(Some("General"), "FutureKey"), Some("1"));
+        assert_eq!(saved.get_from(Some("FutureSection"), "Key"), Some("Value"));
+        assert_eq!(saved.get_from(Some("Mods"), "Sol"), Some("True"));
+    }
+}
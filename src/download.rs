@@ -1,22 +1,158 @@
-use std::{fs::File, path::PathBuf, io::Cursor};
+use std::{fs::File, path::PathBuf, io::Write, sync::{Arc, Mutex}, thread::{self, JoinHandle}, time::{Duration, Instant}};
 use error_chain::error_chain;
+use lazy_static::lazy_static;
 use tempfile::{Builder, TempDir};
+use url::Url;
+use crate::helpers;
 
 error_chain! {
     foreign_links {
         Io(std::io::Error);
         HttpRequest(reqwest::Error);
+        UrlParse(url::ParseError);
     }
+
+    errors {
+        InvalidScheme(scheme: String) {
+            description("invalid url scheme")
+            display("Unsupported url scheme '{}'! Only http and https links can be downloaded.", scheme)
+        }
+        TimedOut(url: String) {
+            description("request timed out")
+            display("The server at {} stopped responding! Try again later.", url)
+        }
+    }
+}
+
+const CONNECT_TIMEOUT_SECONDS: u64 = 15;
+/// How long a request may go without receiving data. Slow downloads are fine as long as data keeps arriving.
+const READ_TIMEOUT_SECONDS: u64 = 30;
+const MAX_REDIRECTS: usize = 10;
+
+lazy_static! {
+    // Every request builds its own runtime, so idle connections are not pooled across them.
+    static ref CLIENT: std::result::Result<reqwest::Client, String> = reqwest::Client::builder()
+        .user_agent(format!("GGXrdModManager/{} (+https://github.com/WistfulHopes/ggxrd-mod-manager)", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECONDS))
+        .redirect(reqwest::redirect::Policy::limited(MAX_REDIRECTS))
+        .pool_max_idle_per_host(0)
+        .build()
+        .map_err(|e| e.to_string());
+}
+
+/// The client shared by all requests, with timeouts and a user agent that names the mod manager.
+fn client() -> Result<reqwest::Client> {
+    CLIENT.clone().map_err(|e| format!("Could not create HTTP client! {}", e).into())
+}
+
+/// Reports timeouts as `TimedOut`, so callers can offer to try again.
+fn request_error(e: reqwest::Error, url: &str) -> Error {
+    match e.is_timeout() {
+        true => ErrorKind::TimedOut(url.to_owned()).into(),
+        false => e.into(),
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct DownloadSettings {
+    pub temp_dir: Option<PathBuf>,
+    pub worker_threads: Option<usize>,
+    pub max_bytes_per_sec: Option<u64>,
+}
+
+impl DownloadSettings {
+    pub fn create_temp_dir(&self) -> std::io::Result<TempDir> {
+        let mut builder = Builder::new();
+        builder.prefix("xrdmodman");
+        match &self.temp_dir {
+            Some(dir) => builder.tempdir_in(dir),
+            None => builder.tempdir(),
+        }
+    }
+
+    fn runtime(&self) -> std::io::Result<tokio::runtime::Runtime> {
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        if let Some(threads) = self.worker_threads {
+            builder.worker_threads(threads.max(1));
+        }
+        builder.enable_all().build()
+    }
+
+    pub fn validate(&self) -> std::io::Result<()> {
+        self.create_temp_dir()?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub bytes_per_sec: u64,
+}
+
+pub struct DownloadTask {
+    pub url: String,
+    progress: Arc<Mutex<DownloadProgress>>,
+    handle: JoinHandle<Result<(PathBuf, TempDir)>>,
 }
 
-pub fn download_mod(url: String) -> Result<(PathBuf, TempDir)> {
-    let result = tokio::runtime::Builder::new_multi_thread()
-    .enable_all()
-    .build()
-    .unwrap()
+impl DownloadTask {
+    pub fn start(url: String, settings: DownloadSettings) -> DownloadTask {
+        let progress = Arc::new(Mutex::new(DownloadProgress::default()));
+        let thread_progress = progress.clone();
+        let thread_url = url.clone();
+        let handle = thread::spawn(move || {
+            let start = Instant::now();
+            download_mod_with_progress(thread_url, &settings, |downloaded, total| {
+                let elapsed = start.elapsed().as_secs_f64();
+                let bytes_per_sec = match elapsed > 0. {
+                    true => (downloaded as f64 / elapsed) as u64,
+                    false => 0,
+                };
+                *thread_progress.lock().unwrap() = DownloadProgress { downloaded, total, bytes_per_sec };
+            })
+        });
+        DownloadTask { url, progress, handle }
+    }
+
+    pub fn progress(&self) -> DownloadProgress {
+        *self.progress.lock().unwrap()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    pub fn join(self) -> Result<(PathBuf, TempDir)> {
+        match self.handle.join() {
+            Ok(result) => result,
+            Err(_) => Err("Download thread panicked!".into()),
+        }
+    }
+}
+
+pub fn validate_url(url: &str) -> Result<Url> {
+    let url = Url::parse(url.trim())?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        scheme => Err(ErrorKind::InvalidScheme(scheme.to_owned()).into()),
+    }
+}
+
+pub fn download_mod(url: String, settings: &DownloadSettings) -> Result<(PathBuf, TempDir)> {
+    download_mod_with_progress(url, settings, |_, _| ())
+}
+
+pub fn download_mod_with_progress(url: String, settings: &DownloadSettings, mut on_progress: impl FnMut(u64, Option<u64>)) -> Result<(PathBuf, TempDir)> {
+    let result = settings.runtime()?
     .block_on(async {
-        let tmp_dir = Builder::new().prefix("xrdmodman").tempdir()?;
-        let response = reqwest::get(url).await?;
+        let tmp_dir = settings.create_temp_dir()?;
+        let read_timeout = Duration::from_secs(READ_TIMEOUT_SECONDS);
+        let mut response = match tokio::time::timeout(read_timeout, client()?.get(&url).send()).await {
+            Ok(response) => response.map_err(|e| request_error(e, &url))?,
+            Err(_) => return Err(ErrorKind::TimedOut(url).into()),
+        };
 
         let name: PathBuf;
 
@@ -27,17 +163,104 @@ pub fn download_mod(url: String) -> Result<(PathBuf, TempDir)> {
                 .and_then(|segments| segments.last())
                 .and_then(|name: &str| if name.is_empty() { None } else { Some(name) })
                 .unwrap_or("tmp.bin");
-            
-            let fname = tmp_dir.path().join(fname);
+
+            let fname = helpers::unique_file_path(tmp_dir.path(), fname);
             name = fname.clone();
             File::create(fname)?
         };
 
-        let mut content =  Cursor::new(response.bytes().await?);
-        std::io::copy(&mut content, &mut dest)?;
+        let total = response.content_length();
+        let mut downloaded: u64 = 0;
+        let start = Instant::now();
+        on_progress(downloaded, total);
+        loop {
+            let chunk = match tokio::time::timeout(read_timeout, response.chunk()).await {
+                Ok(Ok(Some(chunk))) => chunk,
+                Ok(Ok(None)) => break,
+                Ok(Err(e)) => return Err(request_error(e, &url)),
+                Err(_) => return Err(ErrorKind::TimedOut(url).into()),
+            };
+            dest.write_all(&chunk)?;
+            downloaded += chunk.len() as u64;
+            on_progress(downloaded, total);
+            if let Some(limit) = settings.max_bytes_per_sec {
+                let expected = Duration::from_secs_f64(downloaded as f64 / limit as f64);
+                let elapsed = start.elapsed();
+                if expected > elapsed {
+                    tokio::time::sleep(expected - elapsed).await;
+                }
+            }
+        }
 
         Ok((name, tmp_dir))
     });
 
     result
-}
\ No newline at end of file
+}
+
+pub struct PageCheckResult {
+    pub name: String,
+    pub page: String,
+    pub status: std::result::Result<u16, String>,
+}
+
+impl PageCheckResult {
+    pub fn is_taken_down(&self) -> bool {
+        matches!(self.status, Ok(404) | Ok(410))
+    }
+}
+
+pub struct PageCheckTask {
+    progress: Arc<Mutex<(usize, usize)>>,
+    handle: JoinHandle<Result<Vec<PageCheckResult>>>,
+}
+
+impl PageCheckTask {
+    pub fn start(pages: Vec<(String, String)>, settings: DownloadSettings) -> PageCheckTask {
+        let progress = Arc::new(Mutex::new((0, pages.len())));
+        let thread_progress = progress.clone();
+        let handle = thread::spawn(move || {
+            check_pages(pages, &settings, |checked, total| {
+                *thread_progress.lock().unwrap() = (checked, total);
+            })
+        });
+        PageCheckTask { progress, handle }
+    }
+
+    pub fn progress(&self) -> (usize, usize) {
+        *self.progress.lock().unwrap()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    pub fn join(self) -> Result<Vec<PageCheckResult>> {
+        match self.handle.join() {
+            Ok(result) => result,
+            Err(_) => Err("Page check thread panicked!".into()),
+        }
+    }
+}
+
+pub fn check_pages(pages: Vec<(String, String)>, settings: &DownloadSettings, mut on_progress: impl FnMut(usize, usize)) -> Result<Vec<PageCheckResult>> {
+    settings.runtime()?
+    .block_on(async {
+        let client = client()?;
+        let total = pages.len();
+        let mut results = Vec::new();
+        on_progress(0, total);
+        for (checked, (name, page)) in pages.into_iter().enumerate() {
+            let status = match validate_url(&page) {
+                Ok(url) => match client.head(url).timeout(Duration::from_secs(READ_TIMEOUT_SECONDS)).send().await {
+                    Ok(response) => Ok(response.status().as_u16()),
+                    Err(e) => Err(request_error(e, &page).to_string()),
+                },
+                Err(e) => Err(e.to_string()),
+            };
+            results.push(PageCheckResult { name, page, status });
+            on_progress(checked + 1, total);
+        }
+        Ok(results)
+    })
+}
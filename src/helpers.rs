@@ -1,35 +1,839 @@
-use std::{path::Path, io, fs};
-use self_update::cargo_crate_version;
+use std::{path::{Path, PathBuf}, io::{self, Read, Write}, fs, collections::{HashMap, HashSet}};
+use globset::GlobSet;
+use ini::{Ini, Properties};
+use regex::Regex;
+use crate::mod_data::ModData;
+use sha2::{Digest, Sha256};
+use self_update::{cargo_crate_version, update::{Release, ReleaseUpdate}};
+
+pub fn ensure_writable_dir(path: impl AsRef<Path>) -> io::Result<()> {
+    fs::create_dir_all(&path)?;
+    tempfile::tempfile_in(&path)?;
+    Ok(())
+}
+
+/// Writes the ini next to `path` first and renames it into place, so an interrupted write never
+/// leaves a truncated file behind.
+pub fn write_ini_atomic(ini: &Ini, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut buffer: Vec<u8> = Vec::new();
+    ini.write_to(&mut buffer)?;
+    write_file_atomic(path, &buffer)
+}
+
+pub fn write_file_atomic(path: impl AsRef<Path>, contents: &[u8]) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    let mut file = fs::File::create(&temp_path)?;
+    file.write_all(contents)?;
+    // Flush to disk first, or a crash right after the rename can still leave an empty file.
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&temp_path, path)
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct CopyStats {
+    pub files: usize,
+    pub bytes: u64,
+    pub skipped_links: usize,
+    pub linked: usize,
+    pub linked_bytes: u64,
+}
+
+/// Files smaller than this are always copied, since hashing them costs more than it saves.
+const MIN_LINK_SIZE: u64 = 1024 * 1024;
+
+/// Remembers the files copied so far by size and content hash, so identical files can be hardlinked.
+#[derive(Default)]
+pub struct LinkCache {
+    files: HashMap<(u64, Vec<u8>), PathBuf>,
+}
+
+fn hash_file(path: &Path) -> io::Result<Vec<u8>> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Windows limits regular paths to 260 characters, which deeply nested mods copied into
+/// `Mods/<order>/<name>` can exceed. Absolute paths prefixed with `\\?\` are not limited.
+pub fn long_path(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    if !cfg!(windows) || !path.is_absolute() {
+        return path.to_path_buf()
+    }
+    PathBuf::from(extended_length_path(&path.to_string_lossy()))
+}
+
+/// Prefixes an absolute Windows path with `\\?\`, or `\\?\UNC\` for network shares.
+fn extended_length_path(text: &str) -> String {
+    if text.starts_with(r"\\?\") {
+        return text.to_owned()
+    }
+    // Extended-length paths are passed to the file system as is, so they need backslashes.
+    let text = text.replace('/', "\\");
+    match text.strip_prefix(r"\\") {
+        Some(share) => format!(r"\\?\UNC\{}", share),
+        None => format!(r"\\?\{}", text),
+    }
+}
+
+/// Adds the offending path to errors caused by a path that is still too long for Windows.
+fn long_path_error(e: io::Error, path: &Path) -> io::Error {
+    const ERROR_FILENAME_EXCED_RANGE: i32 = 206;
+    match e.raw_os_error() {
+        Some(ERROR_FILENAME_EXCED_RANGE) if cfg!(windows) => io::Error::new(e.kind(), format!("The path {} is too long! Shorten the mod or folder names. {}", path.display(), e)),
+        _ => e,
+    }
+}
 
 pub fn copy_recursively(source: impl AsRef<Path>, destination: impl AsRef<Path>) -> io::Result<()> {
-    fs::create_dir_all(&destination)?;
+    copy_recursively_excluding(source, destination, &GlobSet::empty())?;
+    Ok(())
+}
+
+/// Moves a file or folder. Renaming fails across drives, so fall back to copying and removing the source.
+pub fn move_path(source: impl AsRef<Path>, destination: impl AsRef<Path>) -> io::Result<()> {
+    let (source, destination) = (long_path(source), long_path(destination));
+    if fs::rename(&source, &destination).is_ok() {
+        return Ok(())
+    }
+    match source.is_dir() {
+        true => copy_recursively(&source, &destination).and_then(|_| fs::remove_dir_all(&source)),
+        false => fs::copy(&source, &destination).and_then(|_| fs::remove_file(&source)),
+    }
+}
+
+pub fn copy_recursively_excluding(source: impl AsRef<Path>, destination: impl AsRef<Path>, exclude: &GlobSet) -> io::Result<CopyStats> {
+    copy_recursively_linked(source, destination, exclude, None)
+}
+
+pub fn copy_recursively_linked(source: impl AsRef<Path>, destination: impl AsRef<Path>, exclude: &GlobSet, mut links: Option<&mut LinkCache>) -> io::Result<CopyStats> {
+    let (source, destination) = (long_path(source), long_path(destination));
+    let mut stats = CopyStats::default();
+    let mut visited = HashSet::new();
+    visited.insert(fs::canonicalize(&source)?);
+    copy_filtered(&source, &source, &destination, exclude, &mut stats, &mut visited, &mut links, &mut |_| ())?;
+    Ok(stats)
+}
+
+/// Like `copy_recursively_linked`, but calls `on_progress` after every file with the bytes copied so far
+/// and the total size of the files to copy, which is measured before copying starts.
+pub fn copy_recursively_with_progress(source: impl AsRef<Path>, destination: impl AsRef<Path>, exclude: &GlobSet, mut links: Option<&mut LinkCache>, mut on_progress: impl FnMut(u64, u64)) -> io::Result<CopyStats> {
+    let (source, destination) = (long_path(source), long_path(destination));
+    let total = dir_size(&source, exclude)?;
+    let mut copied = 0;
+    let mut stats = CopyStats::default();
+    let mut visited = HashSet::new();
+    visited.insert(fs::canonicalize(&source)?);
+    copy_filtered(&source, &source, &destination, exclude, &mut stats, &mut visited, &mut links, &mut |bytes| {
+        copied += bytes;
+        on_progress(copied.min(total), total);
+    })?;
+    Ok(stats)
+}
+
+/// Returns the total size of the files under `source` that are not matched by `exclude`.
+pub fn dir_size(source: impl AsRef<Path>, exclude: &GlobSet) -> io::Result<u64> {
+    let source = long_path(source);
+    let mut total = 0;
+    for file in list_files(&source)? {
+        if file.ancestors().any(|path| exclude.is_match(path)) {
+            continue
+        }
+        // Like copy_filtered, skip what can't be read, such as broken links, instead of failing.
+        if let Ok(metadata) = fs::metadata(source.join(&file)) {
+            total += metadata.len();
+        }
+    }
+    Ok(total)
+}
+
+fn copy_file(source: &Path, destination: &Path, stats: &mut CopyStats, links: &mut Option<&mut LinkCache>) -> io::Result<()> {
+    let links = match links {
+        Some(links) => links,
+        None => {
+            stats.bytes += fs::copy(source, destination)?;
+            return Ok(())
+        }
+    };
+    let size = fs::metadata(source)?.len();
+    if size < MIN_LINK_SIZE {
+        stats.bytes += fs::copy(source, destination)?;
+        return Ok(())
+    }
+    let key = (size, hash_file(source)?);
+    if let Some(existing) = links.files.get(&key) {
+        // Hardlinks fail across volumes and on some file systems, so fall back to copying.
+        if fs::hard_link(existing, destination).is_ok() {
+            stats.linked += 1;
+            stats.linked_bytes += size;
+            return Ok(())
+        }
+    }
+    stats.bytes += fs::copy(source, destination)?;
+    links.files.entry(key).or_insert_with(|| destination.to_path_buf());
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn copy_filtered(root: &Path, source: &Path, destination: &Path, exclude: &GlobSet, stats: &mut CopyStats, visited: &mut HashSet<PathBuf>, links: &mut Option<&mut LinkCache>, progress: &mut dyn FnMut(u64)) -> io::Result<()> {
+    fs::create_dir_all(destination).map_err(|e| long_path_error(e, destination))?;
     for entry in fs::read_dir(source)? {
         let entry = entry?;
-        let filetype = entry.file_type()?;
+        let path = entry.path();
+        if exclude.is_match(path.strip_prefix(root).unwrap_or(&path)) {
+            continue;
+        }
+        let mut filetype = entry.file_type()?;
+        if filetype.is_symlink() {
+            // Symlinks and junctions are followed once. Broken links and links back into
+            // a folder that is already being copied are skipped to avoid endless recursion.
+            match fs::metadata(&path) {
+                Ok(metadata) => filetype = metadata.file_type(),
+                Err(_) => {
+                    stats.skipped_links += 1;
+                    continue;
+                }
+            }
+        }
         if filetype.is_dir() {
-            copy_recursively(entry.path(), destination.as_ref().join(entry.file_name()))?;
+            if !visited.insert(fs::canonicalize(&path)?) {
+                stats.skipped_links += 1;
+                continue;
+            }
+            copy_filtered(root, &path, &destination.join(entry.file_name()), exclude, stats, visited, links, progress)?;
         } else {
-            fs::copy(entry.path(), destination.as_ref().join(entry.file_name()))?;
+            let before = stats.bytes + stats.linked_bytes;
+            let target = destination.join(entry.file_name());
+            copy_file(&path, &target, stats, links).map_err(|e| long_path_error(e, &target))?;
+            stats.files += 1;
+            progress(stats.bytes + stats.linked_bytes - before);
         }
     }
     Ok(())
 }
 
-fn add1_char(c: char) -> char {
-    std::char::from_u32(c as u32 + 1).unwrap_or(c)
+/// Returns the files under `source` that are missing from `destination` or have a different size there.
+pub fn verify_copy(source: impl AsRef<Path>, destination: impl AsRef<Path>, exclude: &GlobSet) -> io::Result<Vec<PathBuf>> {
+    let (source, destination) = (long_path(source), long_path(destination));
+    let mut failed = Vec::new();
+    for file in list_files(&source)? {
+        if file.ancestors().any(|path| exclude.is_match(path)) {
+            continue
+        }
+        let expected = fs::metadata(source.join(&file))?.len();
+        match fs::metadata(destination.join(&file)) {
+            Ok(metadata) if metadata.len() == expected => (),
+            _ => failed.push(file),
+        }
+    }
+    Ok(failed)
 }
 
-pub fn add1_str(s: &str) -> String {
-    s.chars().map(add1_char).collect()
+#[derive(Default)]
+pub struct DirDiff {
+    pub added: Vec<PathBuf>,
+    pub changed: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
 }
 
-pub fn update() -> Result<self_update::Status, self_update::errors::Error> {
+impl DirDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+pub fn list_files(root: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_files(root.as_ref(), Path::new(""), &mut files)?;
+    Ok(files)
+}
+
+fn collect_files(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        let path = relative.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn files_differ(old: &Path, new: &Path) -> io::Result<bool> {
+    let (old_meta, new_meta) = (fs::metadata(old)?, fs::metadata(new)?);
+    if old_meta.len() != new_meta.len() {
+        return Ok(true)
+    }
+    if old_meta.modified().ok() == new_meta.modified().ok() {
+        return Ok(false)
+    }
+    Ok(fs::read(old)? != fs::read(new)?)
+}
+
+pub fn diff_dirs(old: impl AsRef<Path>, new: impl AsRef<Path>) -> io::Result<DirDiff> {
+    let (old, new) = (old.as_ref(), new.as_ref());
+    let old_files: HashSet<PathBuf> = list_files(old)?.into_iter().collect();
+    let new_files: HashSet<PathBuf> = list_files(new)?.into_iter().collect();
+    let mut diff = DirDiff::default();
+    for file in &new_files {
+        if !old_files.contains(file) {
+            diff.added.push(file.clone());
+        } else if files_differ(&old.join(file), &new.join(file))? {
+            diff.changed.push(file.clone());
+        }
+    }
+    diff.removed = old_files.difference(&new_files).cloned().collect();
+    diff.added.sort();
+    diff.changed.sort();
+    diff.removed.sort();
+    Ok(diff)
+}
+
+pub struct VanillaComparison {
+    pub replaced: Vec<PathBuf>,
+    pub added: Vec<PathBuf>,
+}
+
+pub fn compare_with_vanilla(mod_path: impl AsRef<Path>, cooked_path: impl AsRef<Path>) -> io::Result<VanillaComparison> {
+    let vanilla: HashSet<String> = list_files(&cooked_path)?
+        .into_iter()
+        .filter(|file| !file.starts_with("Mods"))
+        .filter_map(|file| file.file_name().map(|name| name.to_string_lossy().to_lowercase()))
+        .collect();
+    let mut comparison = VanillaComparison { replaced: Vec::new(), added: Vec::new() };
+    for file in list_files(mod_path)? {
+        let name = file.file_name().map(|name| name.to_string_lossy().to_lowercase()).unwrap_or_default();
+        match vanilla.contains(&name) {
+            true => comparison.replaced.push(file),
+            false => comparison.added.push(file),
+        }
+    }
+    Ok(comparison)
+}
+
+pub fn has_script_package(mod_path: impl AsRef<Path>, script: &str) -> bool {
+    match list_files(mod_path) {
+        Ok(files) => files.iter().any(|file| {
+            let stem_matches = file.file_stem().map_or(false, |stem| stem.to_string_lossy().eq_ignore_ascii_case(script));
+            let extension_matches = file.extension().map_or(false, |ext| ext.eq_ignore_ascii_case("u") || ext.eq_ignore_ascii_case("upk"));
+            stem_matches && extension_matches
+        }),
+        Err(_) => false,
+    }
+}
+
+const MAX_FOLDER_NAME_LENGTH: usize = 64;
+
+pub fn sanitize_folder_name(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .take(MAX_FOLDER_NAME_LENGTH)
+        .collect();
+    sanitized = sanitized.trim().trim_end_matches('.').trim_end().to_owned();
+    if sanitized.is_empty() {
+        return "New Mod".to_owned()
+    }
+    let reserved = ["CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+        "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9"];
+    let base = sanitized.split('.').next().unwrap_or_default();
+    if reserved.iter().any(|name| name.eq_ignore_ascii_case(base)) {
+        sanitized.insert(0, '_');
+    }
+    sanitized
+}
+
+pub fn unescape_ini(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('0') => unescaped.push('\0'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+const MOD_LIST_HEADER: &str = "# GUILTY GEAR Xrd mod list\n# One enabled mod folder name per line, in load order. Lines starting with # are ignored.\n";
+
+/// Writes a plain text mod list that other tools can read: one folder name per line, in load order.
+pub fn write_mod_list(path: &Path, folders: &[String]) -> io::Result<()> {
+    let mut contents = MOD_LIST_HEADER.to_owned();
+    for folder in folders {
+        contents += folder;
+        contents += "\n";
+    }
+    fs::write(path, contents)
+}
+
+pub fn read_mod_list(path: &Path) -> io::Result<Vec<String>> {
+    let contents = fs::read_to_string(path)?;
+    Ok(contents.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_owned())
+        .collect())
+}
+
+pub struct Collection {
+    pub name: String,
+    pub mods: Vec<String>,
+}
+
+pub fn read_collection(path: &Path) -> Option<Collection> {
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    if let Ok(manifest) = Ini::load_from_file_noescape(path.join("collection.ini")) {
+        let name = manifest.section(Some("Collection"))
+            .and_then(|section| section.get("Name"))
+            .map(|name| name.to_owned())
+            .unwrap_or(name);
+        let mods = manifest.section(Some("Mods"))
+            .map(|section| section.get_all("Mod").map(|folder| folder.to_owned()).collect())
+            .unwrap_or_default();
+        return Some(Collection { name, mods })
+    }
+    if ModData::metadata_file(path).exists() {
+        return None
+    }
+    let mut mods: Vec<String> = fs::read_dir(path).ok()?
+        .flatten()
+        .filter(|entry| ModData::metadata_file(&entry.path()).is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    if mods.len() < 2 {
+        return None
+    }
+    mods.sort();
+    Some(Collection { name, mods })
+}
+
+fn numbered_name(stem: &str, extension: Option<&str>, number: usize) -> String {
+    match (number, extension) {
+        (1, Some(extension)) => format!("{}.{}", stem, extension),
+        (1, None) => stem.to_owned(),
+        (_, Some(extension)) => format!("{} ({}).{}", stem, number, extension),
+        (_, None) => format!("{} ({})", stem, number),
+    }
+}
+
+fn first_free_name(dir: &Path, stem: &str, extension: Option<&str>) -> String {
+    let mut number = 1;
+    loop {
+        let name = numbered_name(stem, extension, number);
+        if !dir.join(&name).exists() {
+            return name
+        }
+        number += 1;
+    }
+}
+
+pub fn unique_file_path(dir: &Path, file_name: &str) -> PathBuf {
+    let path = Path::new(file_name);
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_else(|| file_name.to_owned());
+    let extension = path.extension().map(|extension| extension.to_string_lossy().into_owned());
+    dir.join(first_free_name(dir, &stem, extension.as_deref()))
+}
+
+pub fn unique_dir_name(dir: &Path, name: &str) -> String {
+    first_free_name(dir, name, None)
+}
+
+pub fn mod_folder_name(index: usize, count: usize) -> String {
+    let width = count.saturating_sub(1).to_string().len().max(3);
+    format!("{:0width$}", index, width = width)
+}
+
+/// Rewrites the +NativePackages entries of Engine.ScriptPackages to exactly REDGame followed by `scripts`
+/// in order. Returns the entries that were removed and the ones that were added.
+pub fn sync_script_packages(section: &mut Properties, scripts: &[String]) -> (Vec<String>, Vec<String>) {
+    let current: Vec<String> = section.get_all("+NativePackages").map(|package| package.to_owned()).collect();
+    let mut desired = vec!["REDGame".to_owned()];
+    for script in scripts {
+        if !desired.iter().any(|package| package.eq_ignore_ascii_case(script)) {
+            desired.push(script.clone());
+        }
+    }
+    for _ in section.remove_all("+NativePackages") {}
+    for package in &desired {
+        section.append("+NativePackages", package);
+    }
+    let removed = current.iter().filter(|package| !desired.contains(package)).cloned().collect();
+    let added = desired.iter().filter(|package| !current.contains(package)).cloned().collect();
+    (removed, added)
+}
+
+pub fn folder_from_template(template: &str, order: &str, name: &str, author: &str) -> PathBuf {
+    let rendered = template
+        .replace("{order}", order)
+        .replace("{name}", name)
+        .replace("{author}", author);
+    rendered
+        .split(|c| c == '/' || c == '\\')
+        .filter(|component| !component.trim().is_empty())
+        .map(sanitize_folder_name)
+        .collect()
+}
+
+fn updater() -> Result<Box<dyn ReleaseUpdate>, self_update::errors::Error> {
     self_update::backends::github::Update::configure()
         .repo_owner("WistfulHopes")
         .repo_name("ggxrd-mod-manager")
         .bin_name("ggxrd-mod-manager.exe")
         .show_download_progress(true)
+        .no_confirm(true)
         .current_version(cargo_crate_version!())
-        .build()?
-        .update()
+        .build()
+}
+
+pub fn check_update() -> Result<Option<Release>, self_update::errors::Error> {
+    let release = updater()?.get_latest_release()?;
+    match self_update::version::bump_is_greater(cargo_crate_version!(), &release.version)? {
+        true => Ok(Some(release)),
+        false => Ok(None),
+    }
+}
+
+pub fn update() -> Result<self_update::Status, self_update::errors::Error> {
+    updater()?.update()
+}
+
+pub fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::new();
+    for (index, c) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(c);
+    }
+    formatted
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} {}", bytes, UNITS[0]),
+        _ => format!("{:.1} {}", size, UNITS[unit]),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SplitKind {
+    SevenZip,
+    Rar,
+}
+
+pub struct SplitArchive {
+    pub kind: SplitKind,
+    pub base_name: String,
+    pub parts: Vec<PathBuf>,
+    pub missing: Vec<usize>,
+}
+
+impl SplitArchive {
+    pub fn first(&self) -> Option<&PathBuf> {
+        match self.missing.contains(&1) {
+            true => None,
+            false => self.parts.first(),
+        }
+    }
+}
+
+pub fn split_archive(path: &Path) -> Option<SplitArchive> {
+    let file_name = path.file_name()?.to_string_lossy().into_owned();
+    let patterns = [
+        (SplitKind::SevenZip, Regex::new(r"(?i)^(.+)\.7z\.(\d+)$").unwrap()),
+        (SplitKind::Rar, Regex::new(r"(?i)^(.+)\.part(\d+)\.rar$").unwrap()),
+    ];
+    let (kind, pattern) = patterns.into_iter().find(|(_, pattern)| pattern.is_match(&file_name))?;
+    let base_name = pattern.captures(&file_name)?[1].to_owned();
+
+    let mut volumes: Vec<(usize, PathBuf)> = Vec::new();
+    for entry in fs::read_dir(path.parent()?).ok()?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if let Some(captures) = pattern.captures(&name) {
+            if captures[1] == base_name {
+                if let Ok(number) = captures[2].parse::<usize>() {
+                    volumes.push((number, entry.path()));
+                }
+            }
+        }
+    }
+    volumes.sort();
+    let last = volumes.last().map(|(number, _)| *number).unwrap_or_default();
+    let mut missing: Vec<usize> = (1..=last).filter(|number| !volumes.iter().any(|(volume, _)| volume == number)).collect();
+    if kind == SplitKind::SevenZip && missing.is_empty() {
+        missing.extend(missing_last_volumes(&volumes, last));
+    }
+    Some(SplitArchive {
+        kind,
+        base_name,
+        parts: volumes.into_iter().map(|(_, path)| path).collect(),
+        missing,
+    })
+}
+
+/// A 7z archive ends with its header, and the signature header at the start of the first volume
+/// records where that is. Returns the numbers of the volumes needed to reach it that are missing
+/// after `last`, assuming they are as large as the first one.
+fn missing_last_volumes(volumes: &[(usize, PathBuf)], last: usize) -> Vec<usize> {
+    let first = match volumes.first() {
+        Some((_, first)) => first,
+        None => return Vec::new(),
+    };
+    let mut header = [0; 32];
+    let first_size = match fs::File::open(first).and_then(|mut file| file.read_exact(&mut header).and_then(|_| file.metadata())) {
+        Ok(metadata) => metadata.len(),
+        Err(_) => return Vec::new(),
+    };
+    if !header.starts_with(&[b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C]) {
+        return Vec::new()
+    }
+    let next_header_offset = u64::from_le_bytes(header[12..20].try_into().unwrap());
+    let next_header_size = u64::from_le_bytes(header[20..28].try_into().unwrap());
+    let declared = 32u64.saturating_add(next_header_offset).saturating_add(next_header_size);
+    let actual: u64 = volumes.iter().filter_map(|(_, path)| fs::metadata(path).ok()).map(|metadata| metadata.len()).sum();
+    if actual >= declared || first_size == 0 {
+        return Vec::new()
+    }
+    let count = (declared - actual).div_ceil(first_size) as usize;
+    (last + 1..=last + count).collect()
+}
+
+pub fn join_files(parts: &[PathBuf], destination: impl AsRef<Path>) -> io::Result<()> {
+    let mut output = fs::File::create(destination)?;
+    for part in parts {
+        io::copy(&mut fs::File::open(part)?, &mut output)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use globset::{Glob, GlobSetBuilder};
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn copy_skips_excluded_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (source, destination) = (dir.path().join("source"), dir.path().join("destination"));
+        write(&source.join("Mod.upk"), "package");
+        write(&source.join("readme.txt"), "readme");
+        write(&source.join("docs/guide.md"), "guide");
+        write(&source.join("Textures/notes.txt"), "notes");
+        write(&source.join("Textures/Sol.upk"), "texture");
+        let mut exclude = GlobSetBuilder::new();
+        exclude.add(Glob::new("*.txt").unwrap());
+        exclude.add(Glob::new("docs").unwrap());
+        let exclude = exclude.build().unwrap();
+
+        let stats = copy_recursively_excluding(&source, &destination, &exclude).unwrap();
+
+        assert_eq!(stats.files, 2);
+        assert!(destination.join("Mod.upk").is_file());
+        assert!(destination.join("Textures/Sol.upk").is_file());
+        assert!(!destination.join("readme.txt").exists());
+        assert!(!destination.join("docs").exists());
+        assert!(!destination.join("Textures/notes.txt").exists());
+        assert!(verify_copy(&source, &destination, &exclude).unwrap().is_empty());
+    }
+
+    #[test]
+    fn move_path_moves_files_and_folders() {
+        let dir = tempfile::tempdir().unwrap();
+        let (source, destination) = (dir.path().join("source"), dir.path().join("destination"));
+        write(&source.join("Sol/Mod.upk"), "package");
+        write(&source.join("mods.txt"), "Sol");
+        fs::create_dir_all(&destination).unwrap();
+
+        move_path(source.join("Sol"), destination.join("Sol")).unwrap();
+        move_path(source.join("mods.txt"), destination.join("mods.txt")).unwrap();
+
+        assert_eq!(fs::read_to_string(destination.join("Sol/Mod.upk")).unwrap(), "package");
+        assert_eq!(fs::read_to_string(destination.join("mods.txt")).unwrap(), "Sol");
+        assert!(!source.join("Sol").exists());
+        assert!(!source.join("mods.txt").exists());
+    }
+
+    #[test]
+    fn copy_progress_adds_up_to_dir_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let (source, destination) = (dir.path().join("source"), dir.path().join("destination"));
+        write(&source.join("Mod.upk"), "package");
+        write(&source.join("readme.txt"), "readme");
+        write(&source.join("Textures/Sol.upk"), "texture data");
+        let mut exclude = GlobSetBuilder::new();
+        exclude.add(Glob::new("*.txt").unwrap());
+        let exclude = exclude.build().unwrap();
+        let mut progress = Vec::new();
+
+        let stats = copy_recursively_with_progress(&source, &destination, &exclude, None, |copied, total| progress.push((copied, total))).unwrap();
+
+        let total = dir_size(&source, &exclude).unwrap();
+        assert_eq!(total, 19);
+        assert_eq!(progress.len(), stats.files);
+        assert!(progress.iter().all(|(_, reported)| *reported == total));
+        let mut steps: Vec<u64> = progress.iter().scan(0, |last, (copied, _)| {
+            let step = copied - *last;
+            *last = *copied;
+            Some(step)
+        }).collect();
+        steps.sort();
+        assert_eq!(steps, [7, 12]);
+        assert_eq!(progress.last().unwrap().0, total);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_skips_self_referencing_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let (source, destination) = (dir.path().join("source"), dir.path().join("destination"));
+        write(&source.join("Mod.upk"), "package");
+        std::os::unix::fs::symlink(&source, source.join("Loop")).unwrap();
+
+        let stats = copy_recursively_linked(&source, &destination, &GlobSet::empty(), None).unwrap();
+
+        assert_eq!(stats.files, 1);
+        assert_eq!(stats.skipped_links, 1);
+        assert!(destination.join("Mod.upk").is_file());
+        assert!(!destination.join("Loop").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dir_size_skips_broken_links() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("Mod.upk"), "package");
+        std::os::unix::fs::symlink(dir.path().join("Missing.upk"), dir.path().join("Broken.upk")).unwrap();
+
+        assert_eq!(dir_size(dir.path(), &GlobSet::empty()).unwrap(), 7);
+    }
+
+    #[test]
+    fn extended_length_paths() {
+        assert_eq!(extended_length_path(r"C:\Games\GGXrd\Mods"), r"\\?\C:\Games\GGXrd\Mods");
+        assert_eq!(extended_length_path("C:/Games/GGXrd/Mods"), r"\\?\C:\Games\GGXrd\Mods");
+        assert_eq!(extended_length_path(r"\\server\share\Mods"), r"\\?\UNC\server\share\Mods");
+        assert_eq!(extended_length_path(r"\\?\C:\Mods"), r"\\?\C:\Mods");
+    }
+
+    #[test]
+    fn copy_handles_paths_longer_than_260_characters() {
+        let dir = tempfile::tempdir().unwrap();
+        let (source, destination) = (dir.path().join("source"), dir.path().join("destination"));
+        let mut deep = source.clone();
+        while deep.as_os_str().len() <= 300 {
+            deep.push("CookedPCConsole_Textures");
+        }
+        write(&deep.join("Sol.upk"), "texture");
+
+        let stats = copy_recursively_linked(&source, &destination, &GlobSet::empty(), None).unwrap();
+
+        assert_eq!(stats.files, 1);
+        let copied = destination.join(deep.strip_prefix(&source).unwrap()).join("Sol.upk");
+        assert!(copied.as_os_str().len() > 260);
+        assert!(copied.is_file());
+        assert!(verify_copy(&source, &destination, &GlobSet::empty()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn split_7z_detects_missing_last_volume() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut first = vec![b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C, 0, 4, 0, 0, 0, 0];
+        first.extend(250u64.to_le_bytes());
+        first.extend(18u64.to_le_bytes());
+        first.resize(100, 0);
+        fs::write(dir.path().join("Mod.7z.001"), &first).unwrap();
+        fs::write(dir.path().join("Mod.7z.002"), [0; 100]).unwrap();
+
+        let split = split_archive(&dir.path().join("Mod.7z.001")).unwrap();
+        assert_eq!(split.missing, vec![3]);
+
+        fs::write(dir.path().join("Mod.7z.003"), [0; 100]).unwrap();
+        let split = split_archive(&dir.path().join("Mod.7z.001")).unwrap();
+        assert!(split.missing.is_empty());
+    }
+
+    fn script_packages(packages: &[&str]) -> Properties {
+        let mut section = Properties::new();
+        section.insert("+NonNativePackages", "IpDrv");
+        for package in packages {
+            section.append("+NativePackages", *package);
+        }
+        section
+    }
+
+    fn native_packages(section: &Properties) -> Vec<&str> {
+        section.get_all("+NativePackages").collect()
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn sync_script_packages_removes_duplicates() {
+        let mut section = script_packages(&["REDGame", "SolMod", "SolMod"]);
+        let (removed, added) = sync_script_packages(&mut section, &strings(&["SolMod", "solmod", "KyMod"]));
+        assert_eq!(native_packages(&section), ["REDGame", "SolMod", "KyMod"]);
+        assert!(removed.is_empty());
+        assert_eq!(added, ["KyMod"]);
+    }
+
+    #[test]
+    fn sync_script_packages_removes_stale_entries() {
+        let mut section = script_packages(&["REDGame", "OldMod", "SolMod"]);
+        let (removed, added) = sync_script_packages(&mut section, &strings(&["SolMod"]));
+        assert_eq!(native_packages(&section), ["REDGame", "SolMod"]);
+        assert_eq!(removed, ["OldMod"]);
+        assert!(added.is_empty());
+        assert_eq!(section.get("+NonNativePackages"), Some("IpDrv"));
+    }
+
+    #[test]
+    fn sync_script_packages_restores_redgame() {
+        let mut section = script_packages(&["SolMod"]);
+        let (removed, added) = sync_script_packages(&mut section, &strings(&["SolMod"]));
+        assert_eq!(native_packages(&section), ["REDGame", "SolMod"]);
+        assert!(removed.is_empty());
+        assert_eq!(added, ["REDGame"]);
+    }
 }
@@ -0,0 +1,39 @@
+use std::{fs, io, path::Path};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+pub const MAX_SNAPSHOTS: usize = 10;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub timestamp: String,
+    pub mods: Vec<(String, bool)>,
+}
+
+impl Snapshot {
+    pub fn new(mods: Vec<(String, bool)>) -> Snapshot {
+        Snapshot {
+            timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            mods,
+        }
+    }
+
+    pub fn enabled_count(&self) -> usize {
+        self.mods.iter().filter(|(_, enabled)| *enabled).count()
+    }
+}
+
+pub fn load(path: impl AsRef<Path>) -> io::Result<Vec<Snapshot>> {
+    let contents = fs::read_to_string(path)?;
+    serde_json::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn save(path: impl AsRef<Path>, history: &[Snapshot]) -> io::Result<()> {
+    let contents = serde_json::to_string_pretty(history).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(path, contents)
+}
+
+pub fn push(history: &mut Vec<Snapshot>, snapshot: Snapshot) {
+    history.insert(0, snapshot);
+    history.truncate(MAX_SNAPSHOTS);
+}
@@ -1,12 +1,21 @@
 use std::{fs::{OpenOptions, File}, io::Write};
 use chrono::prelude::*;
+use serde::Serialize;
 
 #[derive(Default)]
 pub struct Log {
     pub log_file: Option<File>,
+    pub json_file: Option<File>,
     pub log_text: String,
 }
 
+#[derive(Serialize)]
+struct LogEntry<'a> {
+    ts: String,
+    level: &'a str,
+    message: &'a str,
+}
+
 pub enum LogType {
     Info,
     Warn,
@@ -38,6 +47,19 @@ impl Log {
             }
     }
 
+    pub fn init_json_log(&mut self, append: bool)
+    {
+        match OpenOptions::new()
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .create(true)
+            .open("Launch.jsonl") {
+                Ok(file) => self.json_file = Some(file),
+                Err(e) => self.add_to_log(LogType::Error, format!("Failed to create JSON log file! {}", e)),
+            }
+    }
+
     pub fn add_to_log(&mut self, log_type: LogType, log_data: String)
     {
         let datetime = Local::now();
@@ -55,6 +77,18 @@ impl Log {
             self.log_file.as_mut().unwrap().write(&new_text.as_bytes()).unwrap_or_default();
         }
 
+        if let Some(json_file) = self.json_file.as_mut() {
+            let level = match log_type {
+                LogType::Info => "info",
+                LogType::Warn => "warn",
+                LogType::Error => "error",
+            };
+            let entry = LogEntry { ts: datetime.to_rfc3339(), level, message: &log_data };
+            if let Ok(line) = serde_json::to_string(&entry) {
+                writeln!(json_file, "{}", line).unwrap_or_default();
+            }
+        }
+
         println!("{}", new_text);
         self.log_text += &new_text;
     }
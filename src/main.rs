@@ -1,23 +1,57 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::{path::{PathBuf, Path}, fs::{self}, ffi::OsStr, io::Cursor, process::{Command, exit}, sync::Mutex};
+use std::{path::{Component, PathBuf, Path}, fs::{self}, process::{Command, exit}, sync::Mutex, collections::{HashMap, HashSet}, hash::{Hash, Hasher}, time::{Duration, Instant, SystemTime}};
+use chrono::{Datelike, Local, TimeZone};
+use apply::ApplyTask;
+use cli::{CliArgs, OneClickRequest};
+use config::{CollisionPolicy, Config, LaunchMethod, Schedule, Theme};
+use history::Snapshot;
 use lazy_static::lazy_static;
 use egui::{self, text::LayoutJob, TextFormat, FontId, FontFamily, Color32, Ui, RichText};
-use egui_dnd::{DragDropUi, utils::shift_vec};
+use egui_dnd::{DragDropUi, Handle, utils::shift_vec};
+use egui_extras::{TableBuilder, Column};
+use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use ini::{Ini, EscapePolicy};
+use download::{DownloadTask, DownloadSettings, PageCheckTask};
 use log::{Log, LogType};
-use mod_data::ModData;
-use self_update::cargo_crate_version;
+use mod_data::{ModData, ModIniError};
+use self_update::{cargo_crate_version, update::Release};
 use single_instance::SingleInstance;
 use steamlocate::SteamDir;
 use sysinfo::{System, SystemExt};
 use tempfile::TempDir;
+use tray::{Tray, TrayAction};
 use winreg::{RegKey, enums::{RegDisposition::{REG_CREATED_NEW_KEY, REG_OPENED_EXISTING_KEY}, HKEY_CURRENT_USER}};
 
 mod mod_data;
+mod apply;
+mod archive;
+mod backup;
 mod log;
 mod helpers;
 mod download;
+mod cli;
+mod history;
+mod config;
+mod tray;
+mod template;
+
+const DEFAULT_APP_ID: u32 = 520440;
+const GAME_PROCESS_NAME: &str = "GuiltyGearXrd.exe";
+const QUICK_EXIT_SECONDS: u64 = 10;
+const LAUNCH_TIMEOUT_SECONDS: u64 = 120;
+const THEME_CHECK_SECONDS: u64 = 5;
+const DIAGNOSTICS_LOG_LINES: usize = 50;
+const ORDER_UNDO_DEPTH: usize = 50;
+const APPLY_PROGRESS_MIN_BYTES: u64 = 100 * 1024 * 1024;
+const CONFIG_FLUSH_MILLIS: u64 = 300;
+const MOD_ICON_SIZE: u32 = 16;
+const DEFAULT_FOLDER_TEMPLATE: &str = "{order}/{name}";
+/// Steam launch options suggested for modded play. `-nostartupmovies` skips the intro videos
+/// so restarts while testing mods are quicker, and `-USEALLAVAILABLECORES` lets the engine
+/// use every CPU core when loading the extra packages mods add.
+const RECOMMENDED_LAUNCH_OPTIONS: &str = "-nostartupmovies -USEALLAVAILABLECORES";
 
 lazy_static! {
     static ref CONFIG: Mutex<ConfigState> = Mutex::new(ConfigState::default());
@@ -50,45 +84,90 @@ fn main() -> Result<(), eframe::Error> {
     };
     let mut manager: Box<ManagerState> = Box::<ManagerState>::default();
 
-    manager.console_visible = true;
-    
     let modmanager_instance = SingleInstance::new("e3ff4d30-0d65-45c2-8afd-8bff90d8569a").unwrap();
     let is_running: bool = !modmanager_instance.is_single();
 
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() > 2 && args[1] == "-download" {
-        manager.update_mods();
-        if is_running {
-            manager.append_log();
+    let args = match CliArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            manager.log.append_log();
+            manager.log.add_to_log(LogType::Error, e.clone());
+            eprintln!("{}", e);
+            exit(1)
         }
-        else {
-            manager.init_log();
+    };
+    let headless = args.quiet || args.download.is_some();
+
+    if is_running && !headless && !args.has_commands() {
+        return Ok(())
+    }
+
+    if args.quiet {
+        manager.log.add_to_log(LogType::Info, "Launched GUILTY GEAR Xrd Mod Manager in quiet mode.".to_owned());
+    }
+    else if is_running {
+        manager.append_log();
+    }
+    else {
+        manager.init_log();
+    }
+    manager.init_settings();
+    if CONFIG.lock().unwrap().config.general.json_log && !args.quiet {
+        manager.log.init_json_log(is_running);
+    }
+    manager.init_history();
+    manager.update_mods();
+    manager.apply_schedules();
+
+    if let Some(request) = &args.download {
+        match (&request.item_type, &request.item_id) {
+            (Some(item_type), Some(item_id)) => manager.log.add_to_log(LogType::Info, format!("Downloading {} {} from {}.", item_type, item_id, request.url)),
+            _ => manager.log.add_to_log(LogType::Info, format!("Downloading mod from {}.", request.url)),
         }
-        match prepare_download(args[2].to_owned()) {
+        manager.update_mods();
+        match prepare_download(request, &manager.download_settings) {
             Ok((path, _tempdir)) => {
                 let mut config: std::sync::MutexGuard<ConfigState> = CONFIG.lock().unwrap();
-                manager.install_mod(path, &mut config);
+                if manager.install_mod(path, &mut config).is_err() {
+                    exit(1)
+                }
             }
             Err(e) => manager.log.add_to_log(LogType::Error, format!("Could not download mod! {}", e))
         }
+    }
 
+    if args.download.is_some() || args.has_commands() {
         manager.update_mods();
+        manager.run_commands(&args);
         let mut config: std::sync::MutexGuard<ConfigState> = CONFIG.lock().unwrap();
         manager.set_mod_order_config(&mut config);
         manager.write_config(&mut config);
+    }
 
-        return Ok(())
+    if args.launch {
+        manager.init_steam();
+        manager.setup_mods_and_play();
     }
-    else if is_running {
+
+    if headless || is_running {
         return Ok(())
     }
 
-    manager.init_log();
-    manager.init_update();
-    manager.init_steam();
-    match manager.init_registry() {
-        Ok(_) => manager.log.add_to_log(LogType::Info, "Successfully changed registry!".to_owned()),
-        Err(e) => manager.log.add_to_log(LogType::Info, format!("Failed to change registry! {}", e)),
+    if !args.no_update {
+        manager.init_update();
+    }
+    if !args.launch {
+        manager.init_steam();
+    }
+    manager.interactive = true;
+    if manager.register_protocol {
+        match manager.init_registry() {
+            Ok(_) => manager.log.add_to_log(LogType::Info, "Successfully changed registry!".to_owned()),
+            Err(e) => {
+                manager.log.add_to_log(LogType::Info, format!("Failed to change registry! {}", e));
+                manager.offer_elevation(&e, "The mod manager could not register itself as the handler for xrdmodman: links.");
+            }
+        }
     }
 
     eframe::run_native(
@@ -98,10 +177,8 @@ fn main() -> Result<(), eframe::Error> {
     )
 }
 
-fn prepare_download (line: String) -> Result<(PathBuf, TempDir), Box<dyn std::error::Error>> {
-    let new_line = line.replace("xrdmodman:", "");
-    let parts: Vec<&str> = new_line.split(",").collect();
-    Ok(download::download_mod(parts[0].to_owned())?)
+fn prepare_download (request: &OneClickRequest, settings: &DownloadSettings) -> Result<(PathBuf, TempDir), Box<dyn std::error::Error>> {
+    Ok(download::download_mod(request.url.clone(), settings)?)
 }
 
 #[derive(Default)]
@@ -114,19 +191,180 @@ struct ManagerState {
     selected_mod: ModData,
     log: Log,
     console_visible: bool,
+    console_height: Option<f32>,
+    ui_scale: f32,
+    high_contrast: bool,
+    diagnostics_report: String,
+    url_input: String,
+    download_task: Option<DownloadTask>,
+    running_apply: Option<RunningApply>,
+    download_settings: DownloadSettings,
+    pending_reinstall: Option<PendingReinstall>,
+    pending_restore: Option<PendingRestore>,
+    interactive: bool,
+    last_manifest: String,
+    custom_mods_path: Option<PathBuf>,
+    pending_mods_path: Option<PathBuf>,
+    config_recovery_report: Option<String>,
+    mods_dir_error: Option<String>,
+    auto_update: bool,
+    available_update: Option<Release>,
+    app_id: u32,
+    launch_options: String,
+    history: Vec<Snapshot>,
+    search_query: String,
+    table_view: bool,
+    grouped_view: bool,
+    group_dnd: HashMap<String, DragDropUi>,
+    create_scripts_dnd: DragDropUi,
+    edit_scripts_dnd: DragDropUi,
+    table_sort: Option<(SortColumn, bool)>,
+    state_filter: StateFilter,
+    scripts_only: bool,
+    max_enabled_mods: usize,
+    auto_refresh_seconds: u64,
+    refresh_requested: bool,
+    last_refresh: Option<Instant>,
+    import_report: Option<String>,
+    elevation_reason: Option<String>,
+    install_report: Option<Vec<(PathBuf, Result<(), String>)>>,
+    incompatibility_warnings: HashSet<(String, String)>,
+    incompatibility_report: Option<String>,
+    clean_check: Option<Vec<String>>,
+    editing_metadata: Option<PathBuf>,
+    page_check_task: Option<PageCheckTask>,
+    missing_pages: HashSet<String>,
+    requirement_warnings: HashSet<String>,
+    empty_warnings: HashSet<String>,
+    duplicate_warnings: HashSet<String>,
+    close_to_tray: bool,
+    verify_apply: bool,
+    hardlink_duplicates: bool,
+    write_mod_json: bool,
+    preserve_unmanaged_mods: bool,
+    compressed_storage: bool,
+    register_protocol: bool,
+    block_incompatible_mods: bool,
+    folder_template: String,
+    tray: Option<Tray>,
+    hide_to_tray: bool,
+    quit_requested: bool,
+    launch_watch: Option<LaunchWatch>,
+    crash_prompt_open: bool,
+    edit_confirm_close: bool,
+    vanilla_report: Option<String>,
+    preview_textures: HashMap<PathBuf, Option<egui::TextureHandle>>,
+    icon_textures: HashMap<(PathBuf, Option<SystemTime>), Option<egui::TextureHandle>>,
+    default_icon: Option<egui::TextureHandle>,
+    preview_open: Option<PathBuf>,
+    theme: Theme,
+    launch_method: LaunchMethod,
+    collision_policy: CollisionPolicy,
+    system_dark: bool,
+    applied_high_contrast: Option<bool>,
+    ui_scale_dragging: bool,
+    last_theme_check: Option<Instant>,
+    console_color_overrides: [Option<Color32>; 3],
+    order_undo: Vec<Vec<(String, bool)>>,
+    mod_templates: Vec<template::ModTemplate>,
+    create_template: usize,
+    order_redo: Vec<Vec<(String, bool)>>,
+    had_focus: bool,
+    clipboard_url: Option<String>,
+    last_clipboard_text: String,
+    config_dirty: bool,
+    config_save_failed: bool,
+    last_config_write: Option<Instant>,
+}
+
+struct LaunchWatch {
+    launched: Instant,
+    started: Option<Instant>,
+    last_check: Instant,
+}
+
+struct PendingReinstall {
+    name: String,
+    mod_path: PathBuf,
+    staging: TempDir,
+    /// A copy of the new archive, kept when the installed mod is stored compressed.
+    packed: Option<(TempDir, PathBuf)>,
+    diff: helpers::DirDiff,
+}
+
+/// What to do once the mods are copied.
+#[derive(Clone, Copy)]
+enum ApplyPurpose {
+    Play,
+    SafeLaunch,
+    Sandbox,
+}
+
+struct RunningApply {
+    task: ApplyTask,
+    mod_datas: Vec<ModData>,
+    game_path: PathBuf,
+    game_mods_path: PathBuf,
+    purpose: ApplyPurpose,
+    start: Instant,
+    // Compressed mods stay extracted until they are copied.
+    _unpacked: Vec<TempDir>,
+}
+
+struct PendingRestore {
+    path: PathBuf,
+    entries: Vec<(backup::BackupEntry, bool)>,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+enum StateFilter {
+    #[default]
+    All,
+    Enabled,
+    Disabled,
+    Empty,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SortColumn {
+    Enabled,
+    Name,
+    Author,
+    Category,
+    Version,
+    Installed,
 }
 
 #[derive(Default)]
 struct ConfigState {
-    config: Ini,
+    config: Config,
 }
 
 #[derive(Default)]
 struct WindowState {
     about_open: bool,
+    console_colors_open: bool,
+    diagnostics_open: bool,
+    url_open: bool,
+    launch_options_open: bool,
     create_open: bool,
     edit_open: bool,
     remove_open: bool,
+    reload_requested: bool,
+    edit_metadata_requested: bool,
+    move_request: Option<MoveAction>,
+    compare_requested: bool,
+    bulk_exclude_toggled: bool,
+    known_good_toggled: bool,
+    export_requested: bool,
+}
+
+#[derive(Clone, Copy)]
+enum MoveAction {
+    Top,
+    Up,
+    Down,
+    Bottom,
 }
 
 impl ManagerState {
@@ -158,16 +396,58 @@ impl ManagerState {
         new_key.set_value("", &(r#"""#.to_owned() + &exe_path.display().to_string() + r#"""# + command))
     }
 
+    fn remove_registry(&mut self) -> std::io::Result<()> {
+        let hkcr = RegKey::predef(HKEY_CURRENT_USER);
+        let path = Path::new("Software").join("Classes").join("xrdmodman");
+        match hkcr.delete_subkey_all(&path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
     fn init_update(&mut self) {
+        let last_installed = CONFIG.lock().unwrap().config.general.last_installed_version.clone();
+        if let Some(version) = last_installed.filter(|version| version != cargo_crate_version!()) {
+            // The last update claimed to install this version, but we are still running an older one.
+            // Don't update automatically again, or a broken update would restart forever.
+            self.log.add_to_log(LogType::Warn, format!("Version {} was installed, but version {} is running! Skipping automatic update.", version, cargo_crate_version!()));
+            let mut config = CONFIG.lock().unwrap();
+            config.config.general.last_installed_version = None;
+            self.write_config(&mut config);
+            return
+        }
+        if self.auto_update {
+            return self.run_update()
+        }
+        match helpers::check_update() {
+            Ok(Some(release)) => {
+                self.log.add_to_log(LogType::Info, format!("Version {} is available!", release.version));
+                self.available_update = Some(release);
+            }
+            Ok(None) => self.log.add_to_log(LogType::Info, "You are on the latest version!".to_owned()),
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Update check failed! {}", e)),
+        }
+    }
+
+    fn run_update(&mut self) {
         match helpers::update() {
             Ok(status) => {
                 match status {
                     self_update::Status::UpToDate(_) => self.log.add_to_log(LogType::Info, "You are on the latest version!".to_owned()),
-                    self_update::Status::Updated(_) => 
+                    self_update::Status::Updated(version) => 
                     {
-                        self.log.add_to_log(LogType::Info, "Update successful! Restarting...".to_owned());
-                        Command::new("ggxrd-mod-manager.exe").spawn().unwrap();
-                        exit(0)
+                        self.log.add_to_log(LogType::Info, format!("Updated to version {}! Restarting...", version));
+                        let mut config = CONFIG.lock().unwrap();
+                        self.set_mod_order_config(&mut config);
+                        config.config.general.last_installed_version = Some(version);
+                        self.write_config(&mut config);
+                        self.flush_config(&mut config);
+                        drop(config);
+                        let restart = std::env::current_exe().and_then(|exe_path| Command::new(exe_path).spawn());
+                        match restart {
+                            Ok(_) => exit(0),
+                            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not restart the mod manager! Please restart it manually. {}", e)),
+                        }
                     }
                 }
             }
@@ -179,41 +459,12 @@ impl ManagerState {
     {
         let mut config_needs_update = false;
         let mut edit_flag = false;
+        let icon_paths: Vec<Option<PathBuf>> = self.mod_datas.iter().map(|mod_data| mod_data.icon_path()).collect();
+        let mut icons = icon_paths.into_iter().map(|path| self.mod_icon(ui.ctx(), path)).collect::<Vec<_>>().into_iter();
         let response = self.dnd.ui::<ModData>(ui, self.mod_datas.iter_mut(), |mod_data, ui, handle| {
-            ui.horizontal(|ui| {
-                if ui.checkbox(&mut mod_data.enabled, "").changed() {
-                    update_mod_config(mod_data.name.clone(), mod_data);
-                    config_needs_update = true;
-                };
-                let response = ui.selectable_label(true, &mod_data.name);
-                if response.clicked() {
-                    self.selected_mod = mod_data.clone();
-                }
-                let popup_id = ui.make_persistent_id(format!("right_click_menu_{}", mod_data.name));
-                if response.secondary_clicked() {
-                    self.selected_mod = mod_data.clone();
-                    ui.memory_mut(|mem|{
-                        mem.toggle_popup(popup_id)
-                    });
-                }
-                egui::popup::popup_below_widget(ui, popup_id, &response, |ui| {
-                    let mut window = WINDOW.lock().unwrap();
-                    ui.set_min_width(150.);
-                    if ui.button("Open containing folder").clicked() {
-                        open::that(mod_data.path.clone()).unwrap_or_default();
-                    }
-                    if ui.button("Edit mod").clicked() {
-                        window.edit_open = true;
-                        edit_flag = true;
-                    }
-                    if ui.button("Remove mod").clicked() {
-                        window.remove_open = true;
-                    }
-                });
-                handle.ui(ui, mod_data, |ui| {
-                    ui.separator();
-                })
-            });
+            let (changed, edit) = mod_row(ui, handle, mod_data, &mut self.selected_mod, icons.next().as_ref());
+            config_needs_update |= changed;
+            edit_flag |= edit;
         });
         if let Some(completed) = response.completed {
             shift_vec(completed.from, completed.to, &mut self.mod_datas);
@@ -224,536 +475,3224 @@ impl ManagerState {
         }
         (config_needs_update, edit_flag)
     }
-}
 
-fn init_mod_config(mod_name: String, data: &mut ModData, config: &mut ConfigState)
-{
-    let section = config.config.section(Some("Mods"));
-    match section
-    {
-        Some(section) => {
-            let entry: Option<&str> = section.get(&mod_name);
-            match entry {
-                Some(entry) => {
-                    match entry {
-                        "True" => data.enabled = true,
-                        _ => data.enabled = false,
-                    }
-                }
-                None => {
-                    config.config.with_section(Some("Mods")).set(&mod_name, "True");
-                }
+    fn mods_grouped(&mut self, ui: &mut Ui) -> (bool, bool)
+    {
+        let mut config_needs_update = false;
+        let mut edit_flag = false;
+
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+        for (index, mod_data) in self.mod_datas.iter().enumerate() {
+            let category = mod_data.category_name();
+            match groups.iter_mut().find(|(name, _)| *name == category) {
+                Some((_, indices)) => indices.push(index),
+                None => groups.push((category, vec![index])),
             }
         }
-        None => {
-            config.config.with_section(Some("Mods")).set(&mod_name, "True");
-        }
-    }
-}
+        groups.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()));
 
-fn update_mod_config(mod_name: String, data: &mut ModData)
-{
-    let mut config = CONFIG.lock().unwrap();
-    match data.enabled {
-        true => {
-            config.config.with_section(Some("Mods")).set(&mod_name, "True");
-        }
-        false => {
-            config.config.with_section(Some("Mods")).set(&mod_name, "False");
+        let collapsed: Vec<String> = CONFIG.lock().unwrap().config.collapsed_groups.clone();
+        self.category_node(ui, "", &groups, &collapsed, &mut config_needs_update, &mut edit_flag);
+
+        if config_needs_update {
+            for (i, data) in self.mod_datas.iter_mut().enumerate() {
+                data.order = i;
+            }
         }
+        (config_needs_update, edit_flag)
     }
-}
-
-fn remove_mod_config(mod_name: String)
-{
-    let mut config = CONFIG.lock().unwrap();
-    config.config.with_section(Some("Mods")).delete(&mod_name);
-}
 
-impl ManagerState {
-    fn create_config(&mut self, config: &mut ConfigState)
+    /// Shows the categories directly below `parent` as collapsible headers, each with its subcategories
+    /// followed by its own mods. Collapse state is saved per category path.
+    fn category_node(&mut self, ui: &mut Ui, parent: &str, groups: &[(String, Vec<usize>)], collapsed: &[String], config_needs_update: &mut bool, edit_flag: &mut bool)
     {
-        let mut ini = Ini::new();
-        ini.with_section(Some("General"))
-            .set("ConsoleVisible", "True");
-        self.write_config(config)
-    }
+        let prefix = match parent {
+            "" => String::new(),
+            parent => format!("{}/", parent),
+        };
+        let mut children: Vec<String> = Vec::new();
+        for (category, _) in groups {
+            if let Some(rest) = category.strip_prefix(&prefix) {
+                let child = format!("{}{}", prefix, rest.split('/').next().unwrap_or_default());
+                if !children.contains(&child) {
+                    children.push(child);
+                }
+            }
+        }
+        children.sort_by_key(|child| child.to_lowercase());
 
-    fn write_config(&mut self, config: &mut ConfigState)
-    {
-        let mut exe_path = std::env::current_exe().unwrap();
-        exe_path.pop();
-        let ini_path = exe_path.join("config.ini");
-        match config.config.write_to_file(ini_path)
-        {
-            Ok(_) => (),
-            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not create config ini! {}", e))
+        for category in children {
+            let child_prefix = format!("{}/", category);
+            let count: usize = groups.iter()
+                .filter(|(name, _)| *name == category || name.starts_with(&child_prefix))
+                .map(|(_, indices)| indices.len())
+                .sum();
+            let is_collapsed = collapsed.contains(&category);
+            let label = category.rsplit('/').next().unwrap_or_default().to_owned();
+            let header = egui::CollapsingHeader::new(format!("{} ({})", label, count))
+                .id_source(format!("category_{}", category))
+                .open(Some(!is_collapsed))
+                .show(ui, |ui| {
+                    self.category_node(ui, &category, groups, collapsed, config_needs_update, edit_flag);
+                    let indices = match groups.iter().find(|(name, _)| *name == category) {
+                        Some((_, indices)) => indices,
+                        None => return,
+                    };
+                    let icon_paths: Vec<Option<PathBuf>> = indices.iter().map(|index| self.mod_datas[*index].icon_path()).collect();
+                    let mut icons = icon_paths.into_iter().map(|path| self.mod_icon(ui.ctx(), path)).collect::<Vec<_>>().into_iter();
+                    let dnd = self.group_dnd.entry(category.clone()).or_default();
+                    let mut group: Vec<ModData> = indices.iter().map(|index| self.mod_datas[*index].clone()).collect();
+                    let mut group_changed = false;
+                    let response = dnd.ui::<ModData>(ui, group.iter_mut(), |mod_data, ui, handle| {
+                        let (changed, edit) = mod_row(ui, handle, mod_data, &mut self.selected_mod, icons.next().as_ref());
+                        group_changed |= changed;
+                        *edit_flag |= edit;
+                    });
+                    if let Some(completed) = response.completed {
+                        shift_vec(completed.from, completed.to, &mut group);
+                        group_changed = true;
+                    }
+                    if group_changed {
+                        for (index, mod_data) in indices.iter().zip(group) {
+                            self.mod_datas[*index] = mod_data;
+                        }
+                        *config_needs_update = true;
+                    }
+                });
+            if header.header_response.clicked() {
+                let mut config = CONFIG.lock().unwrap();
+                config.config.set_group_collapsed(&category, !is_collapsed);
+                self.write_config(&mut config);
+            }
         }
     }
 
-    fn set_mod_order_config(&mut self, config: &mut ConfigState)
+    fn mods_search(&mut self, ui: &mut Ui) -> (bool, bool)
     {
-        config.config.delete(Some("Mods"));
-        for mod_data in &self.mod_datas {
-            let enabled = match mod_data.enabled {
-                true => "True",
-                false => "False",
+        let mut config_needs_update = false;
+        let mut edit_flag = false;
+
+        let matcher = SkimMatcherV2::default();
+        let query = self.search_query.trim();
+        let state_filter = self.state_filter;
+        let scripts_only = self.scripts_only;
+        let mut matches: Vec<(i64, usize, Vec<usize>)> = self.mod_datas.iter().enumerate()
+            .filter(|(_, mod_data)| match state_filter {
+                StateFilter::All => true,
+                StateFilter::Enabled => mod_data.enabled,
+                StateFilter::Disabled => !mod_data.enabled,
+                StateFilter::Empty => mod_data.empty,
+            })
+            .filter(|(_, mod_data)| !scripts_only || !mod_data.scripts.is_empty())
+            .filter_map(|(index, mod_data)| match query.is_empty() {
+                true => Some((0, index, Vec::new())),
+                false => matcher.fuzzy_indices(&mod_data.name, query)
+                    .map(|(score, indices)| (score, index, indices)),
+            })
+            .collect();
+        matches.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        if matches.is_empty() {
+            match query.is_empty() {
+                true => ui.label("No mods match this filter."),
+                false => ui.label("No mods match your search."),
             };
-            config.config.with_section(Some("Mods"))
-                .set(mod_data.name.clone(), enabled);
         }
-        self.write_config(config)
-    }
-
-    fn init_steam(&mut self)
-    {
-        let steamdir: Option<SteamDir> = SteamDir::locate();
-        match steamdir {
-            Some(mut dir) => {
-                match dir.app(&520440)
-                {
-                    Some(app) => {
-                        self.game_path = app.path.clone();
-                        self.log.add_to_log(LogType::Info, format!("Guilty Gear Xrd Rev 2 located at {}.", app.path.display()))
-                    },
-                    None => self.log.add_to_log(LogType::Error, "Could not locate Guilty Gear Xrd Rev 2! Make sure you have it installed.".to_owned())
+        for (_, index, indices) in matches {
+            let mod_data = &mut self.mod_datas[index];
+            ui.horizontal(|ui| {
+                if ui.checkbox(&mut mod_data.enabled, "").changed() {
+                    update_mod_config(mod_folder_name(mod_data), mod_data);
+                    config_needs_update = true;
+                };
+                let name = highlight_matches(ui, &mod_data.name, &indices);
+                let response = ui.selectable_label(self.selected_mod.path == mod_data.path, name);
+                if response.clicked() || response.secondary_clicked() {
+                    self.selected_mod = mod_data.clone();
                 }
-            },
-            None => self.log.add_to_log(LogType::Error, "Could not locate Steam!".to_owned())
+                edit_flag |= mod_context_menu(ui, &response, mod_data);
+                mod_badges(ui, mod_data);
+            });
         }
+        (config_needs_update, edit_flag)
     }
 
-    fn init_config(&mut self)
+    fn mods_table(&mut self, ui: &mut Ui) -> (bool, bool)
     {
-        let mut config = CONFIG.lock().unwrap();
-        let mut exe_path = std::env::current_exe().unwrap();
-        exe_path.pop();
-        let ini_path = exe_path.join("config.ini");
-        if ini_path.exists() {
-            let ini = Ini::load_from_file_noescape(ini_path);
-            match ini {
-                Ok(ini) => config.config = ini,
-                Err(_) => self.create_config(&mut config),
-            }
-        }
-        else 
-        {
-            self.create_config(&mut config)
-        } 
-    }
+        let mut config_needs_update = false;
+        let mut edit_flag = false;
 
-    fn update_mods(&mut self)
-    {
-        self.init_config();
-        self.mod_datas.clear();
-        let mut dir = std::env::current_exe().unwrap();
-        dir.pop();
-        self.mods_path = Path::join(&dir, "Mods");
-        match fs::create_dir(&self.mods_path)
-        {
-            Ok(_) => (),
-            Err(ref e) => {
-                if e.kind() == std::io::ErrorKind::AlreadyExists {()}
-                else {
-                    self.log.add_to_log(LogType::Error, format!("Could not create Mods directory! {}", e))
+        let mut indices: Vec<usize> = (0..self.mod_datas.len()).collect();
+        if let Some((column, ascending)) = self.table_sort {
+            let mods = &self.mod_datas;
+            indices.sort_by(|a, b| {
+                let (a, b) = (&mods[*a], &mods[*b]);
+                let ordering = match column {
+                    SortColumn::Enabled => b.enabled.cmp(&a.enabled),
+                    SortColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                    SortColumn::Author => a.author.to_lowercase().cmp(&b.author.to_lowercase()),
+                    SortColumn::Category => a.category.to_lowercase().cmp(&b.category.to_lowercase()),
+                    SortColumn::Version => a.version.cmp(&b.version),
+                    SortColumn::Installed => b.installed_at.cmp(&a.installed_at),
+                };
+                match ascending {
+                    true => ordering.then(a.order.cmp(&b.order)),
+                    false => ordering.reverse().then(a.order.cmp(&b.order)),
                 }
-            }
+            });
         }
-        let mut config: std::sync::MutexGuard<ConfigState> = CONFIG.lock().unwrap();
-        let mod_section = config.config.section(Some("Mods"));
-        let mut config_requires_update = false;
-        match mod_section {
-            Some(mod_section) => {
-                for mod_entry in mod_section.iter() {
-                    let path = Path::join(&self.mods_path, mod_entry.0).join("mod.ini");
-                    if path.exists()
-                    {
-                        let mut mod_data = ModData::new();
-                        let ini: Result<Ini, ini::Error> = Ini::load_from_file_noescape(&path);
-                        match ini {
-                            Ok(file) => {
-                                let desc_section: Option<&ini::Properties> = file.section(Some("Description"));
-                                match desc_section {
-                                    Some(desc) => {
-                                        let mod_name: Option<&str> = desc.get("Name");
-                                        match mod_name {
-                                            Some(name) => mod_data.name = name.to_owned(),
-                                            None => {
-                                                self.log.add_to_log(LogType::Warn, format!("The mod ini at path {} doesn't have a name in the desciption section! Ignoring mod.", path.display()));
-                                                continue
-                                            }
-                                        }
-                                        let mod_author = desc.get("Author");
-                                        match mod_author {
-                                            Some(author) => mod_data.author = author.to_owned(),
-                                            None => ()
-                                        }
-                                        let mod_version = desc.get("Version");
-                                        match mod_version {
-                                            Some(version) => mod_data.version = version.to_owned(),
-                                            None => ()
-                                        }
-                                        let mod_category = desc.get("Category");
-                                        match mod_category {
-                                            Some(category) => mod_data.category = category.to_owned(),
-                                            None => ()
-                                        }
-                                        let mod_description = desc.get("Description");
-                                        match mod_description {
-                                            Some(description) => mod_data.description = description.to_owned(),
-                                            None => ()
-                                        }
-                                        let mod_page = desc.get("Page");
-                                        match mod_page {
-                                            Some(page) => mod_data.page = page.to_owned(),
-                                            None => ()
-                                        }
 
-                                        match file.section(Some("Scripts"))
-                                        {
-                                            Some(section) => {
-                                                for script in section.get_all("ScriptPackage")
-                                                {
-                                                    mod_data.scripts.push(script.to_owned());
-                                                }
-                                            }
-                                            None => (),
-                                        }
+        let columns = [
+            (SortColumn::Enabled, "Enabled"),
+            (SortColumn::Name, "Name"),
+            (SortColumn::Author, "Author"),
+            (SortColumn::Category, "Category"),
+            (SortColumn::Version, "Version"),
+            (SortColumn::Installed, "Installed"),
+        ];
 
-                                        mod_data.path = Path::join(&self.mods_path, &mod_name.unwrap());
-                                        mod_data.enabled = match mod_entry.1 {
-                                            "True" => true,
-                                            "False" => false,
-                                            _ => true,
-                                        };
-                                        mod_data.order = self.mod_datas.len();
-                                        self.mod_datas.push(mod_data);
-                                    },
-                                    None => {
-                                        self.log.add_to_log(LogType::Error, format!("The mod ini at path {} doesn't have a description section! Ignoring mod.", path.display()));
-                                        config_requires_update = true;
-                                        continue
-                                    }
-                                }
-                            },
-                            Err(_) => {
-                                self.log.add_to_log(LogType::Error, format!("Ini at path {} does not exist! Ignoring mod.", path.display()));
-                                config_requires_update = true;
-                                continue
-                            }
+        TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .column(Column::auto().at_least(60.))
+            .column(Column::initial(250.).at_least(100.).clip(true))
+            .column(Column::initial(150.).at_least(60.).clip(true))
+            .column(Column::initial(150.).at_least(60.).clip(true))
+            .column(Column::initial(100.).at_least(60.))
+            .column(Column::remainder().at_least(80.))
+            .header(20., |mut header| {
+                for (column, label) in columns {
+                    header.col(|ui| {
+                        let text = match self.table_sort {
+                            Some((sort_column, true)) if sort_column == column => format!("{} ⬆", label),
+                            Some((sort_column, false)) if sort_column == column => format!("{} ⬇", label),
+                            _ => label.to_owned(),
+                        };
+                        if ui.selectable_label(false, RichText::new(text).strong()).clicked() {
+                            self.table_sort = match self.table_sort {
+                                Some((sort_column, ascending)) if sort_column == column => Some((column, !ascending)),
+                                _ => Some((column, true)),
+                            };
                         }
-                    }
-                    else {
-                        self.log.add_to_log(LogType::Error, format!("Path {} does not exist! Ignoring mod.", path.display()));
-                        config_requires_update = true;
-                    }
+                    });
                 }
-            }
-            None => (),
-        }
-        for mod_data in &mut self.mod_datas {
-            init_mod_config(mod_data.name.clone(), mod_data, &mut config);
-        }
-        if config_requires_update {
-            self.set_mod_order_config(&mut config)
-        }
+            })
+            .body(|mut body| {
+                for index in indices {
+                    let mod_data = &mut self.mod_datas[index];
+                    body.row(20., |mut row| {
+                        row.col(|ui| {
+                            if ui.checkbox(&mut mod_data.enabled, "").changed() {
+                                update_mod_config(mod_folder_name(mod_data), mod_data);
+                                config_needs_update = true;
+                            }
+                        });
+                        row.col(|ui| {
+                            let response = ui.selectable_label(self.selected_mod.path == mod_data.path, mod_data.display_name());
+                            if response.clicked() || response.secondary_clicked() {
+                                self.selected_mod = mod_data.clone();
+                            }
+                            edit_flag |= mod_context_menu(ui, &response, mod_data);
+                            mod_badges(ui, mod_data);
+                        });
+                        row.col(|ui| {
+                            ui.label(&mod_data.author);
+                        });
+                        row.col(|ui| {
+                            ui.label(&mod_data.category);
+                        });
+                        row.col(|ui| {
+                            ui.label(&mod_data.version);
+                        });
+                        row.col(|ui| {
+                            let installed = mod_data.installed_at
+                                .and_then(|timestamp| Local.timestamp_opt(timestamp, 0).single())
+                                .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+                                .unwrap_or_default();
+                            ui.label(installed);
+                        });
+                    });
+                }
+            });
+        (config_needs_update, edit_flag)
     }
+}
 
-    fn init_log(&mut self) {
-        self.log.init_log();
-        self.log.add_to_log(LogType::Info, "Launched GUILTY GEAR Xrd Mod Manager.".to_owned());
+struct ScriptItem {
+    index: usize,
+    name: String,
+}
+
+impl Hash for ScriptItem {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state)
     }
+}
 
-    fn append_log(&mut self) {
-        self.log.append_log();
-        self.log.add_to_log(LogType::Info, "Another instance of the mod manager was opened!".to_owned());
+fn scripts_editor(ui: &mut Ui, dnd: &mut DragDropUi, scripts: &mut Vec<String>)
+{
+    let mut items: Vec<ScriptItem> = scripts.drain(..).enumerate()
+        .map(|(index, name)| ScriptItem { index, name })
+        .collect();
+    let response = dnd.ui::<ScriptItem>(ui, items.iter_mut(), |item, ui, handle| {
+        ui.horizontal(|ui| {
+            handle.ui(ui, item, |ui| {
+                ui.label("☰");
+            });
+            ui.text_edit_singleline(&mut item.name);
+        });
+    });
+    if let Some(completed) = response.completed {
+        shift_vec(completed.from, completed.to, &mut items);
     }
+    scripts.extend(items.into_iter().map(|item| item.name));
+}
 
-    fn init_mod(&mut self, name: String, config: &mut ConfigState)
-    {
-        for mod_data in &self.mod_datas {
-            if name == mod_data.name {
-                return
-            }
-        }
+fn highlight_matches(ui: &Ui, text: &str, indices: &[usize]) -> LayoutJob
+{
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    let mut job = LayoutJob::default();
+    for (index, c) in text.chars().enumerate() {
+        let color = match indices.contains(&index) {
+            true => ui.visuals().hyperlink_color,
+            false => ui.visuals().text_color(),
+        };
+        job.append(
+            &c.to_string(),
+            0.0,
+            TextFormat {
+                font_id: font_id.clone(),
+                color,
+                ..Default::default()
+            },
+        );
+    }
+    job
+}
 
-        let path = Path::join(&self.mods_path, &name).join("mod.ini");
-        if path.exists()
-        {
-            let mut mod_data: ModData = ModData::new();
-            let ini: Result<Ini, ini::Error> = Ini::load_from_file_noescape(&path);
-            match ini {
-                Ok(file) => {
-                    let desc_section: Option<&ini::Properties> = file.section(Some("Description"));
-                    match desc_section {
-                        Some(desc) => {
-                            let mod_name = desc.get("Name");
-                            match mod_name {
-                                Some(name) => mod_data.name = name.to_owned(),
-                                None => {
-                                    self.log.add_to_log(LogType::Warn, format!("The mod ini at path {} doesn't have a name in the desciption section! Ignoring mod.", path.display()));
-                                }
-                            }
-                            let mod_author = desc.get("Author");
-                            match mod_author {
-                                Some(author) => mod_data.author = author.to_owned(),
-                                None => ()
-                            }
-                            let mod_version = desc.get("Version");
-                            match mod_version {
-                                Some(version) => mod_data.version = version.to_owned(),
-                                None => ()
-                            }
-                            let mod_category = desc.get("Category");
-                            match mod_category {
-                                Some(category) => mod_data.category = category.to_owned(),
-                                None => ()
-                            }
-                            let mod_description = desc.get("Description");
-                            match mod_description {
-                                Some(description) => mod_data.description = description.to_owned(),
-                                None => ()
-                            }
-                            let mod_page = desc.get("Page");
-                            match mod_page {
-                                Some(page) => mod_data.page = page.to_owned(),
-                                None => ()
-                            }
-                            
-                            match file.section(Some("Scripts"))
-                            {
-                                Some(section) => {
-                                    for script in section.get_all("ScriptPackage")
-                                    {
-                                        mod_data.scripts.push(script.to_owned());
-                                    }
-                                }
-                                None => (),
-                            }
-    
-                            mod_data.path = Path::join(&self.mods_path, &name);
-                            init_mod_config(mod_name.unwrap().to_owned(), &mut mod_data, config);
-                            self.write_config(config);
-                            self.mod_datas.push(mod_data);
-                        },
-                        None => {
-                            mod_data.name = name.clone();
-                            mod_data.path = Path::join(&self.mods_path, &name);
-                            mod_data.write_data().unwrap_or_default();
-                            init_mod_config(name, &mut mod_data, config);
-                            self.write_config(config);
-                            self.mod_datas.push(mod_data);
-                            self.log.add_to_log(LogType::Warn, format!("The mod ini at path {} doesn't have a description section! Created one automatically.", &path.display()));
+fn mod_badges(ui: &mut Ui, mod_data: &ModData)
+{
+    if mod_data.is_new() {
+        ui.label(RichText::new("🆕").color(ui.visuals().hyperlink_color)).on_hover_text("Installed in the last 24 hours.");
+    }
+    if mod_data.page_missing {
+        ui.label(RichText::new("⚠").color(ui.visuals().warn_fg_color))
+            .on_hover_text(format!("The mod page returned not found and was likely taken down.\n{}", mod_data.page));
+    }
+    if mod_data.empty {
+        ui.label(RichText::new("📭").color(ui.visuals().warn_fg_color))
+            .on_hover_text("Empty. The mod folder only contains metadata, so applying it changes nothing.");
+    }
+    if mod_data.packed.is_some() {
+        ui.label("🗜").on_hover_text("Stored compressed. Extracted when applying.");
+    }
+    if let Some(schedule) = mod_data.schedule {
+        ui.label("📅").on_hover_text(format!("Scheduled. Enabled from {} each year when the mod manager starts.", schedule));
+    }
+    if mod_data.exclude_from_bulk {
+        ui.label("🔒").on_hover_text("Skipped by Enable all.");
+    }
+    if mod_data.known_good {
+        ui.label("🛡").on_hover_text("Known good. Included in Safe Launch.");
+    }
+    if !mod_data.conflicts.is_empty() {
+        ui.label(RichText::new("❌").color(ui.visuals().error_fg_color))
+            .on_hover_text(format!("Incompatible with {}.", mod_data.conflicts.join(", ")));
+    }
+    if let Some(requirement) = mod_data.unmet_requirement() {
+        ui.label(RichText::new("⛔").color(ui.visuals().error_fg_color))
+            .on_hover_text(requirement);
+    }
+}
+
+fn mod_row(ui: &mut Ui, handle: Handle, mod_data: &mut ModData, selected_mod: &mut ModData, icon: Option<&egui::TextureHandle>) -> (bool, bool)
+{
+    let mut config_needs_update = false;
+    let mut edit_flag = false;
+    ui.horizontal(|ui| {
+        if ui.checkbox(&mut mod_data.enabled, "").changed() {
+            update_mod_config(mod_folder_name(mod_data), mod_data);
+            config_needs_update = true;
+        };
+        if let Some(icon) = icon {
+            ui.image(icon.id(), fit_size(icon.size_vec2(), egui::vec2(MOD_ICON_SIZE as f32, MOD_ICON_SIZE as f32)));
+        }
+        let response = ui.selectable_label(true, mod_data.display_name());
+        if response.clicked() {
+            *selected_mod = mod_data.clone();
+        }
+        if response.secondary_clicked() {
+            *selected_mod = mod_data.clone();
+        }
+        edit_flag |= mod_context_menu(ui, &response, mod_data);
+        mod_badges(ui, mod_data);
+        handle.ui(ui, mod_data, |ui| {
+            ui.separator();
+        })
+    });
+    (config_needs_update, edit_flag)
+}
+
+fn mod_context_menu(ui: &mut Ui, response: &egui::Response, mod_data: &ModData) -> bool
+{
+    let mut edit_flag = false;
+    let popup_id = ui.make_persistent_id(format!("right_click_menu_{}", mod_data.path.display()));
+    if response.secondary_clicked() {
+        ui.memory_mut(|mem|{
+            mem.toggle_popup(popup_id)
+        });
+    }
+    egui::popup::popup_below_widget(ui, popup_id, response, |ui| {
+        let mut window = WINDOW.lock().unwrap();
+        ui.set_min_width(150.);
+        if ui.button("Open containing folder").clicked() {
+            open::that(mod_data.path.clone()).unwrap_or_default();
+        }
+        if ui.button("Copy path").clicked() {
+            ui.output_mut(|o| o.copied_text = mod_data.path.display().to_string());
+        }
+        if ui.button("Copy mod name").clicked() {
+            ui.output_mut(|o| o.copied_text = mod_data.name.clone());
+        }
+        if ui.button("Reload metadata").clicked() {
+            window.reload_requested = true;
+        }
+        let metadata_file = ModData::metadata_file(&mod_data.path);
+        let metadata_name = metadata_file.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        if ui.button(format!("Edit {}", metadata_name)).on_hover_text("Open the raw metadata in your text editor.").clicked() {
+            window.edit_metadata_requested = true;
+        }
+        if ui.button("Export mod").clicked() {
+            window.export_requested = true;
+        }
+        if ui.button("Compare with game files").clicked() {
+            window.compare_requested = true;
+        }
+        let bulk_label = match mod_data.exclude_from_bulk {
+            true => "Include in Enable all",
+            false => "Exclude from Enable all",
+        };
+        if ui.button(bulk_label).clicked() {
+            window.bulk_exclude_toggled = true;
+        }
+        let known_good_label = match mod_data.known_good {
+            true => "Unmark as known good",
+            false => "Mark as known good",
+        };
+        if ui.button(known_good_label).clicked() {
+            window.known_good_toggled = true;
+        }
+        ui.separator();
+        for (action, label) in [
+            (MoveAction::Top, "Move to top"),
+            (MoveAction::Up, "Move up"),
+            (MoveAction::Down, "Move down"),
+            (MoveAction::Bottom, "Move to bottom"),
+        ] {
+            if ui.button(label).clicked() {
+                window.move_request = Some(action);
+            }
+        }
+        ui.separator();
+        if ui.button("Edit mod").clicked() {
+            window.edit_open = true;
+            edit_flag = true;
+        }
+        if ui.button("Remove mod").clicked() {
+            window.remove_open = true;
+        }
+    });
+    edit_flag
+}
+
+fn console_colors(high_contrast: bool, dark: bool) -> (Color32, Color32, Color32, Color32)
+{
+    match (high_contrast, dark) {
+        (false, _) => (Color32::GREEN, Color32::YELLOW, Color32::RED, Color32::WHITE),
+        (true, true) => (Color32::from_rgb(120, 255, 120), Color32::from_rgb(255, 255, 100), Color32::from_rgb(255, 130, 130), Color32::WHITE),
+        (true, false) => (Color32::from_rgb(0, 90, 0), Color32::from_rgb(110, 60, 0), Color32::from_rgb(160, 0, 0), Color32::BLACK),
+    }
+}
+
+/// Returns each pair of enabled mods where either mod declares the other incompatible.
+fn incompatible_pairs(mod_datas: &[ModData]) -> Vec<(String, String)>
+{
+    let enabled: Vec<&ModData> = mod_datas.iter().filter(|mod_data| mod_data.enabled).collect();
+    let mut pairs = Vec::new();
+    for (index, first) in enabled.iter().enumerate() {
+        for second in &enabled[index + 1..] {
+            if first.declares_incompatible(second) || second.declares_incompatible(first) {
+                pairs.push((first.name.clone(), second.name.clone()));
+            }
+        }
+    }
+    pairs
+}
+
+fn incompatibility_report(pairs: &[(String, String)]) -> String
+{
+    let lines: Vec<String> = pairs.iter().map(|(first, second)| format!("{} and {}", first, second)).collect();
+    format!("These enabled mods are incompatible with each other:\n\n{}\n\nDisable one mod from each pair.", lines.join("\n"))
+}
+
+/// Returns the download URL in `text` if it is an xrdmodman: link, a GameBanana link or a direct link to an archive.
+fn clipboard_mod_url(text: &str) -> Option<String>
+{
+    let text = text.trim();
+    if text.starts_with("xrdmodman:") {
+        return OneClickRequest::parse(text).ok().map(|request| request.url)
+    }
+    let url = download::validate_url(text).ok()?;
+    let gamebanana = url.host_str().map_or(false, |host| host == "gamebanana.com" || host.ends_with(".gamebanana.com"));
+    let archive = [".zip", ".7z", ".rar"].iter().any(|extension| url.path().to_lowercase().ends_with(extension));
+    match gamebanana || archive {
+        true => Some(url.to_string()),
+        false => None,
+    }
+}
+
+fn mod_folder_name(mod_data: &ModData) -> String
+{
+    match mod_data.path.file_name() {
+        Some(folder) => folder.to_string_lossy().into_owned(),
+        None => mod_data.name.clone(),
+    }
+}
+
+fn parse_hex_color(text: &str) -> Option<Color32>
+{
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None
+    }
+    let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+    Some(Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+fn hex_color(color: Color32) -> String
+{
+    format!("#{:02X}{:02X}{:02X}", color.r(), color.g(), color.b())
+}
+
+fn launch_args(launch_options: &str, mod_datas: &[ModData]) -> (Vec<String>, Vec<String>)
+{
+    let mut args: Vec<String> = launch_options.split_whitespace().map(|arg| arg.to_owned()).collect();
+    let mut conflicts: Vec<String> = Vec::new();
+    for mod_data in mod_datas.iter().filter(|mod_data| mod_data.enabled) {
+        for arg in &mod_data.launch_args {
+            if args.contains(arg) {
+                continue
+            }
+            let key = arg.split('=').next().unwrap_or_default();
+            match args.iter().find(|existing| existing.split('=').next().unwrap_or_default().eq_ignore_ascii_case(key)) {
+                Some(existing) => conflicts.push(format!("Mod {} wants launch argument {}, which conflicts with {}. Keeping {}.", mod_data.name, arg, existing, existing)),
+                None => args.push(arg.clone()),
+            }
+        }
+    }
+    (args, conflicts)
+}
+
+fn fit_size(size: egui::Vec2, max: egui::Vec2) -> egui::Vec2
+{
+    let scale = (max.x / size.x).min(max.y / size.y).min(1.);
+    size * scale
+}
+
+fn default_mods_path() -> PathBuf
+{
+    let mut dir = std::env::current_exe().unwrap();
+    dir.pop();
+    Path::join(&dir, "Mods")
+}
+
+/// The folders in the mods folder that hold mods. Hidden folders, like the staging folders used while
+/// extracting archives, are skipped.
+fn mod_folders(mods_path: &Path) -> std::io::Result<Vec<String>>
+{
+    Ok(fs::read_dir(mods_path)?.flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.'))
+        .collect())
+}
+
+fn locate_game(app_id: u32) -> Result<PathBuf, String>
+{
+    let steamdir: Option<SteamDir> = SteamDir::locate();
+    match steamdir {
+        Some(mut dir) => {
+            match dir.app(&app_id)
+            {
+                Some(app) => Ok(app.path.clone()),
+                None => Err("Could not locate Guilty Gear Xrd Rev 2! Make sure you have it installed.".to_owned())
+            }
+        },
+        None => Err("Could not locate Steam!".to_owned())
+    }
+}
+
+fn check_registry() -> Result<String, String>
+{
+    let hkcr = RegKey::predef(HKEY_CURRENT_USER);
+    let path = Path::new("Software").join("Classes").join("xrdmodman").join("shell").join("open").join("command");
+    let key = hkcr.open_subkey(&path).map_err(|e| format!("Could not open command registry key for xrdmodman! {}", e))?;
+    let command: String = key.get_value("").map_err(|e| format!("Could not read command registry key for xrdmodman! {}", e))?;
+    let exe_path = std::env::current_exe().map_err(|e| e.to_string())?;
+    match command.contains(&exe_path.display().to_string()) {
+        true => Ok(format!("xrdmodman links are handled by {}.", exe_path.display())),
+        false => Err(format!("xrdmodman links are handled by another program: {}", command)),
+    }
+}
+
+fn check_script_packages(game_path: &Path) -> Result<String, String>
+{
+    let ini_path = Path::join(game_path, "REDGame").join("Config").join("DefaultEngine.ini");
+    let ini = Ini::load_from_file_noescape(&ini_path).map_err(|e| format!("Could not read DefaultEngine.ini! {}", e))?;
+    match ini.section(Some("Engine.ScriptPackages")) {
+        Some(_) => Ok(format!("Found Engine.ScriptPackages in {}.", ini_path.display())),
+        None => Err("Could not find Engine.ScriptPackages in DefaultEngine.ini! Your game installation may be broken.".to_owned()),
+    }
+}
+
+fn init_mod_config(mod_name: String, data: &mut ModData, config: &mut ConfigState)
+{
+    match config.config.mod_enabled(&mod_name) {
+        Some(enabled) => data.enabled = enabled,
+        None => {
+            data.enabled = config.config.general.new_mods_enabled;
+            config.config.set_mod(&mod_name, data.enabled);
+            config.config.set_installed_at(&mod_name, Local::now().timestamp());
+        }
+    }
+}
+
+/// Copies the per-mod settings stored in the config, which are keyed by mod folder, into the mod.
+fn load_mod_settings(mod_data: &mut ModData, config: &Config)
+{
+    let folder = mod_folder_name(mod_data);
+    mod_data.notes = config.note(&folder).to_owned();
+    mod_data.exclude_from_bulk = config.bulk_excluded.contains(&folder);
+    mod_data.known_good = config.known_good.contains(&folder);
+    mod_data.installed_at = config.installed_at(&folder);
+    mod_data.schedule = config.schedule(&folder);
+    mod_data.apply_stats = config.apply_stats(&folder);
+}
+
+fn update_mod_config(mod_name: String, data: &mut ModData)
+{
+    CONFIG.lock().unwrap().config.set_mod(&mod_name, data.enabled);
+}
+
+fn remove_mod_config(mod_name: String)
+{
+    let mut config = CONFIG.lock().unwrap();
+    config.config.remove_mod(&mod_name);
+    config.config.set_note(&mod_name, "");
+    config.config.set_schedule(&mod_name, None);
+    config.config.clear_apply_stats(&mod_name);
+}
+
+impl ManagerState {
+    fn create_config(&mut self, config: &mut ConfigState)
+    {
+        config.config = Config::new();
+        config.config.general.mods_keyed_by_folder = true;
+        self.write_config(config)
+    }
+
+    fn recover_config(&mut self, ini_path: &Path, error: ini::Error, config: &mut ConfigState)
+    {
+        self.log.add_to_log(LogType::Error, format!("Could not read config ini! {}", error));
+        let contents = match fs::read(ini_path) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Could not read config ini for recovery! {}", e));
+                return self.create_config(config)
+            }
+        };
+        let backup_path = ini_path.with_file_name(format!("config.ini.corrupt-{}", Local::now().format("%Y%m%d-%H%M%S")));
+        match fs::copy(ini_path, &backup_path) {
+            Ok(_) => self.log.add_to_log(LogType::Info, format!("Backed up corrupt config ini to {}.", backup_path.display())),
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not back up corrupt config ini! {}", e)),
+        }
+
+        let mut ini = Ini::new();
+        let mut section: Option<String> = None;
+        let mut salvaged = 0;
+        let mut lost: Vec<String> = Vec::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                section = Some(line[1..line.len() - 1].trim().to_owned());
+                continue
+            }
+            match line.split_once('=') {
+                Some((key, value)) if !key.trim().is_empty() && !key.contains('"') => {
+                    let value = value.trim().trim_matches('"');
+                    ini.with_section(section.clone()).set(key.trim(), value);
+                    salvaged += 1;
+                }
+                _ => lost.push(line.to_owned()),
+            }
+        }
+        config.config = Config::from_ini(&ini);
+        self.write_config(config);
+
+        let mut report = format!("Your config.ini could not be read and was recovered.\nA backup was saved to {}.\n\nSalvaged {} entries.", backup_path.display(), salvaged);
+        match lost.is_empty() {
+            true => report += "\nNo entries were lost.",
+            false => {
+                report += &format!("\nLost {} line(s):", lost.len());
+                for line in &lost {
+                    report += &format!("\n  {}", line);
+                }
+            }
+        }
+        self.log.add_to_log(LogType::Warn, format!("Recovered config ini. Salvaged {} entries, lost {} line(s).", salvaged, lost.len()));
+        self.config_recovery_report = Some(report);
+    }
+
+    fn write_config(&mut self, config: &mut ConfigState)
+    {
+        // While the window is open, writes are batched and flushed by flush_config_debounced.
+        self.config_dirty = true;
+        if !self.interactive {
+            self.flush_config(config);
+        }
+    }
+
+    fn flush_config(&mut self, config: &mut ConfigState)
+    {
+        if !self.config_dirty {
+            return
+        }
+        self.config_dirty = false;
+        self.last_config_write = Some(Instant::now());
+        let mut exe_path = std::env::current_exe().unwrap();
+        exe_path.pop();
+        let ini_path = exe_path.join("config.ini");
+        self.config_save_failed = false;
+        match config.config.save(ini_path)
+        {
+            Ok(_) => (),
+            Err(e) => {
+                self.config_save_failed = true;
+                self.log.add_to_log(LogType::Error, format!("Could not create config ini! {}", e));
+                self.offer_elevation(&e, "The mod manager could not save config.ini next to its executable.");
+            }
+        }
+    }
+
+    fn offer_elevation(&mut self, error: &std::io::Error, reason: &str)
+    {
+        if cfg!(windows) && self.interactive && error.kind() == std::io::ErrorKind::PermissionDenied {
+            self.elevation_reason = Some(reason.to_owned());
+        }
+    }
+
+    /// Starts an elevated copy of the mod manager once this one has exited, then closes this one.
+    fn restart_as_admin(&mut self)
+    {
+        let exe_path = match std::env::current_exe() {
+            Ok(path) => path,
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Could not find the mod manager executable! {}", e));
+                return
+            }
+        };
+        let script = format!("Wait-Process -Id {} -ErrorAction SilentlyContinue; Start-Process -FilePath '{}' -Verb RunAs",
+            std::process::id(), exe_path.display().to_string().replace('\'', "''"));
+        match Command::new("powershell").args(["-NoProfile", "-WindowStyle", "Hidden", "-Command", &script]).spawn() {
+            Ok(_) => {
+                self.log.add_to_log(LogType::Info, "Restarting the mod manager as administrator...".to_owned());
+                let mut config = CONFIG.lock().unwrap();
+                self.set_mod_order_config(&mut config);
+                self.flush_config(&mut config);
+                exit(0)
+            }
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not restart the mod manager as administrator! {}", e)),
+        }
+    }
+
+    /// Checks the clipboard for a mod link whenever the window gains focus, so copying a link
+    /// and switching back offers to install it.
+    fn check_clipboard(&mut self, ctx: &egui::Context)
+    {
+        let has_focus = ctx.input(|i| i.raw.has_focus);
+        let gained_focus = has_focus && !self.had_focus;
+        self.had_focus = has_focus;
+        if !gained_focus || self.download_task.is_some() {
+            return
+        }
+        let text = match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+            Ok(text) => text,
+            Err(_) => return,
+        };
+        // Only offer each copied link once, even if it is dismissed.
+        if text == self.last_clipboard_text {
+            return
+        }
+        self.clipboard_url = clipboard_mod_url(&text);
+        self.last_clipboard_text = text;
+    }
+
+    fn flush_config_debounced(&mut self, ctx: &egui::Context)
+    {
+        if !self.config_dirty {
+            return
+        }
+        let interval = Duration::from_millis(CONFIG_FLUSH_MILLIS);
+        let elapsed = self.last_config_write.map(|time| time.elapsed()).unwrap_or(interval);
+        match interval.checked_sub(elapsed) {
+            Some(remaining) if !remaining.is_zero() => ctx.request_repaint_after(remaining),
+            _ => {
+                let mut config = CONFIG.lock().unwrap();
+                self.flush_config(&mut config);
+                // Repaint once more so the status bar shows the result of the write.
+                ctx.request_repaint();
+            }
+        }
+    }
+
+    fn set_mod_order_config(&mut self, config: &mut ConfigState)
+    {
+        config.config.mods = self.mod_datas.iter().map(|mod_data| (mod_folder_name(mod_data), mod_data.enabled)).collect();
+        self.write_config(config)
+    }
+
+    fn run_commands(&mut self, args: &CliArgs)
+    {
+        let changes = args.enable.iter().map(|name| (name, true)).chain(args.disable.iter().map(|name| (name, false)));
+        for (name, enabled) in changes {
+            match self.mod_datas.iter_mut().find(|data| &data.name == name) {
+                Some(mod_data) => {
+                    mod_data.enabled = enabled;
+                    self.log.add_to_log(LogType::Info, format!("{} mod {}.", if enabled { "Enabled" } else { "Disabled" }, name));
+                }
+                None => self.log.add_to_log(LogType::Error, format!("Could not find mod {}!", name)),
+            }
+        }
+    }
+
+    fn init_steam(&mut self)
+    {
+        match locate_game(self.app_id) {
+            Ok(path) => {
+                self.log.add_to_log(LogType::Info, format!("Guilty Gear Xrd Rev 2 located at {}.", path.display()));
+                self.game_path = path;
+            },
+            Err(e) => self.log.add_to_log(LogType::Error, e)
+        }
+    }
+
+    fn run_diagnostics(&mut self)
+    {
+        let game_check = locate_game(self.app_id).map(|path| format!("Guilty Gear Xrd Rev 2 located at {}.", path.display()));
+        let game_path = match locate_game(self.app_id) {
+            Ok(path) => path,
+            Err(_) => self.game_path.clone(),
+        };
+        let checks: Vec<(&str, Result<String, String>)> = vec![
+            ("Game installation", game_check),
+            ("Protocol handler", check_registry()),
+            ("DefaultEngine.ini", check_script_packages(&game_path)),
+        ];
+
+        let mut report = format!("GUILTY GEAR Xrd Mod Manager {}\n", cargo_crate_version!());
+        let mut failed = 0;
+        for (name, result) in checks {
+            match result {
+                Ok(details) => {
+                    self.log.add_to_log(LogType::Info, format!("Diagnostics: {} passed. {}", name, details));
+                    report += &format!("[PASS] {}: {}\n", name, details);
+                }
+                Err(details) => {
+                    failed += 1;
+                    self.log.add_to_log(LogType::Error, format!("Diagnostics: {} failed! {}", name, details));
+                    report += &format!("[FAIL] {}: {}\n", name, details);
+                }
+            }
+        }
+        match failed {
+            0 => report += "All checks passed!",
+            _ => report += &format!("{} check(s) failed!", failed),
+        }
+        self.diagnostics_report = report;
+        WINDOW.lock().unwrap().diagnostics_open = true;
+    }
+
+    fn diagnostics_summary(&self) -> String
+    {
+        let os = System::new().long_os_version().unwrap_or_else(|| std::env::consts::OS.to_owned());
+        let steam = match SteamDir::locate() {
+            Some(_) => "Yes",
+            None => "No",
+        };
+        let game_path = match locate_game(self.app_id) {
+            Ok(path) => path.display().to_string(),
+            Err(e) => format!("{} (last known: {})", e, self.game_path.display()),
+        };
+        let registry = match (self.register_protocol, check_registry()) {
+            (false, _) => "Disabled".to_owned(),
+            (true, Ok(details)) => details,
+            (true, Err(details)) => details,
+        };
+        let enabled = self.mod_datas.iter().filter(|mod_data| mod_data.enabled).count();
+        let lines: Vec<&str> = self.log.log_text.lines().collect();
+        let recent = lines[lines.len().saturating_sub(DIAGNOSTICS_LOG_LINES)..].join("\n");
+
+        format!("GUILTY GEAR Xrd Mod Manager {}\nOS: {}\nSteam found: {}\nGame path: {}\nMods: {} ({} enabled)\nProtocol handler: {}\n\nLast {} log lines:\n{}\n",
+            cargo_crate_version!(), os, steam, game_path, self.mod_datas.len(), enabled, registry, DIAGNOSTICS_LOG_LINES, recent)
+    }
+
+    fn init_config(&mut self)
+    {
+        let mut config = CONFIG.lock().unwrap();
+        let mut exe_path = std::env::current_exe().unwrap();
+        exe_path.pop();
+        let ini_path = exe_path.join("config.ini");
+        if ini_path.exists() {
+            match Config::load(&ini_path) {
+                Ok(loaded) => config.config = loaded,
+                Err(e) => self.recover_config(&ini_path, e, &mut config),
+            }
+        }
+        else 
+        {
+            self.create_config(&mut config)
+        } 
+    }
+
+    fn init_settings(&mut self)
+    {
+        self.init_config();
+        let config = CONFIG.lock().unwrap();
+        let general = config.config.general.clone();
+        let window = config.config.window.clone();
+        drop(config);
+        self.auto_update = general.auto_update;
+        self.close_to_tray = general.close_to_tray;
+        self.verify_apply = general.verify_apply;
+        self.hardlink_duplicates = general.hardlink_duplicates;
+        self.write_mod_json = general.write_mod_json;
+        self.preserve_unmanaged_mods = general.preserve_unmanaged_mods;
+        self.compressed_storage = general.compressed_storage;
+        self.register_protocol = general.register_protocol;
+        self.block_incompatible_mods = general.block_incompatible_mods;
+        self.folder_template = general.folder_template.clone().unwrap_or_else(|| DEFAULT_FOLDER_TEMPLATE.to_owned());
+        self.console_visible = window.console_visible;
+        self.console_height = window.console_height;
+        self.ui_scale = window.ui_scale.unwrap_or(1.).clamp(0.5, 3.);
+        self.high_contrast = window.high_contrast;
+        let console = CONFIG.lock().unwrap().config.console.clone();
+        let colors = [("Info", console.info_color), ("Warn", console.warn_color), ("Error", console.error_color)];
+        for (index, (key, text)) in colors.into_iter().enumerate() {
+            self.console_color_overrides[index] = text.and_then(|text| match parse_hex_color(&text) {
+                Some(color) => Some(color),
+                None => {
+                    self.log.add_to_log(LogType::Warn, format!("Invalid console color {} for {}! Using the default.", text, key));
+                    None
+                }
+            });
+        }
+        self.theme = general.theme;
+        self.launch_method = general.launch_method;
+        self.collision_policy = general.on_name_collision;
+        self.app_id = DEFAULT_APP_ID;
+        if let Some(app_id) = &general.app_id {
+            match app_id.parse::<u32>() {
+                Ok(app_id) => self.app_id = app_id,
+                Err(e) => self.log.add_to_log(LogType::Warn, format!("Invalid AppId value {}! Using the default. {}", app_id, e)),
+            }
+        }
+        self.launch_options = general.launch_options.clone();
+        self.download_settings = DownloadSettings::default();
+        if let Some(threads) = &general.download_threads {
+            match threads.parse::<usize>() {
+                Ok(threads) if threads > 0 => self.download_settings.worker_threads = Some(threads),
+                _ => self.log.add_to_log(LogType::Warn, format!("Invalid DownloadThreads value {}! Using the default.", threads)),
+            }
+        }
+        if let Some(limit) = &general.max_download_bytes_per_sec {
+            match limit.parse::<u64>() {
+                Ok(0) => (),
+                Ok(limit) => {
+                    self.log.add_to_log(LogType::Info, format!("Limiting downloads to {}/s.", helpers::format_size(limit)));
+                    self.download_settings.max_bytes_per_sec = Some(limit);
+                }
+                Err(_) => self.log.add_to_log(LogType::Warn, format!("Invalid MaxDownloadBytesPerSec value {}! Downloads will not be limited.", limit)),
+            }
+        }
+        self.max_enabled_mods = 0;
+        if let Some(limit) = &general.max_enabled_mods {
+            match limit.parse::<usize>() {
+                Ok(limit) => self.max_enabled_mods = limit,
+                Err(_) => self.log.add_to_log(LogType::Warn, format!("Invalid MaxEnabledMods value {}! Any number of mods can be enabled.", limit)),
+            }
+        }
+        self.auto_refresh_seconds = 0;
+        if let Some(seconds) = &general.auto_refresh_seconds {
+            match seconds.parse::<u64>() {
+                Ok(seconds) => self.auto_refresh_seconds = seconds,
+                Err(_) => self.log.add_to_log(LogType::Warn, format!("Invalid AutoRefreshSeconds value {}! Mods will only be refreshed when they change in the mod manager.", seconds)),
+            }
+        }
+        self.custom_mods_path = None;
+        if let Some(mods_path) = &general.mods_path {
+            match helpers::ensure_writable_dir(mods_path) {
+                Ok(_) => {
+                    self.log.add_to_log(LogType::Info, format!("Using mods folder {}.", mods_path));
+                    self.custom_mods_path = Some(PathBuf::from(mods_path));
+                }
+                Err(e) => self.log.add_to_log(LogType::Warn, format!("Mods folder {} is not writable! Using the default. {}", mods_path, e)),
+            }
+        }
+        if let Some(temp_dir) = &general.temp_dir {
+            let settings = DownloadSettings {
+                temp_dir: Some(PathBuf::from(temp_dir)),
+                ..self.download_settings.clone()
+            };
+            match settings.validate() {
+                Ok(_) => {
+                    self.log.add_to_log(LogType::Info, format!("Using temporary directory {}.", temp_dir));
+                    self.download_settings = settings;
+                }
+                Err(e) => self.log.add_to_log(LogType::Warn, format!("Temporary directory {} is not writable! Using the system default. {}", temp_dir, e)),
+            }
+        }
+    }
+
+    fn history_path() -> PathBuf
+    {
+        let mut exe_path = std::env::current_exe().unwrap();
+        exe_path.pop();
+        exe_path.join("history.json")
+    }
+
+    fn init_history(&mut self)
+    {
+        let path = ManagerState::history_path();
+        if !path.exists() {
+            return
+        }
+        match history::load(&path) {
+            Ok(history) => self.history = history,
+            Err(e) => self.log.add_to_log(LogType::Warn, format!("Could not read setup history! {}", e)),
+        }
+    }
+
+    fn record_history(&mut self)
+    {
+        let mods = self.mod_datas.iter().map(|data| (mod_folder_name(data), data.enabled)).collect();
+        history::push(&mut self.history, Snapshot::new(mods));
+        match history::save(ManagerState::history_path(), &self.history) {
+            Ok(_) => (),
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not write setup history! {}", e)),
+        }
+    }
+
+    fn export_mod_list(&mut self, path: &Path)
+    {
+        let folders: Vec<String> = self.mod_datas.iter()
+            .filter(|mod_data| mod_data.enabled)
+            .map(mod_folder_name)
+            .collect();
+        match helpers::write_mod_list(path, &folders) {
+            Ok(_) => self.log.add_to_log(LogType::Info, format!("Exported {} enabled mod(s) to {}.", folders.len(), path.display())),
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not export mod list to {}! {}", path.display(), e)),
+        }
+    }
+
+    /// Enables the listed mods in the listed order, ahead of the rest, and disables everything else.
+    /// Entries are matched against folder names first and mod names second, ignoring case.
+    fn import_mod_list(&mut self, path: &Path, config: &mut ConfigState)
+    {
+        let names = match helpers::read_mod_list(path) {
+            Ok(names) => names,
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Could not read mod list {}! {}", path.display(), e));
+                return
+            }
+        };
+        let mut imported: Vec<ModData> = Vec::new();
+        let mut unmatched: Vec<String> = Vec::new();
+        for name in &names {
+            let position = self.mod_datas.iter().position(|data| &mod_folder_name(data) == name)
+                .or_else(|| self.mod_datas.iter().position(|data| mod_folder_name(data).eq_ignore_ascii_case(name)))
+                .or_else(|| self.mod_datas.iter().position(|data| data.name.eq_ignore_ascii_case(name)));
+            match position {
+                Some(index) => {
+                    let mut mod_data = self.mod_datas.remove(index);
+                    mod_data.enabled = true;
+                    imported.push(mod_data);
+                }
+                None if imported.iter().any(|data| mod_folder_name(data).eq_ignore_ascii_case(name)) => (),
+                None => unmatched.push(name.clone()),
+            }
+        }
+        for mod_data in &mut self.mod_datas {
+            mod_data.enabled = false;
+        }
+        imported.append(&mut self.mod_datas);
+        for (i, data) in imported.iter_mut().enumerate() {
+            data.order = i;
+        }
+        self.mod_datas = imported;
+        self.set_mod_order_config(config);
+        self.log.add_to_log(LogType::Info, format!("Imported mod list {}. Enabled {} of {} listed mod(s).", path.display(), names.len() - unmatched.len(), names.len()));
+        if !unmatched.is_empty() {
+            for name in &unmatched {
+                self.log.add_to_log(LogType::Warn, format!("Mod list entry {} does not match any installed mod!", name));
+            }
+            self.import_report = Some(format!("These entries in {} do not match any installed mod:\n\n{}", path.display(), unmatched.join("\n")));
+        }
+    }
+
+    /// Reorders the installed mods named in a mod list to match its order. They keep the positions
+    /// they already take up in the list, so unlisted mods stay where they are.
+    fn apply_load_order(&mut self, path: &Path, config: &mut ConfigState)
+    {
+        let names = match helpers::read_mod_list(path) {
+            Ok(names) => names,
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Could not read mod list {}! {}", path.display(), e));
+                return
+            }
+        };
+        let mut ordered: Vec<usize> = Vec::new();
+        let mut unmatched: Vec<String> = Vec::new();
+        for name in &names {
+            let position = self.mod_datas.iter().position(|data| &mod_folder_name(data) == name)
+                .or_else(|| self.mod_datas.iter().position(|data| mod_folder_name(data).eq_ignore_ascii_case(name)))
+                .or_else(|| self.mod_datas.iter().position(|data| data.name.eq_ignore_ascii_case(name)));
+            match position {
+                Some(index) if !ordered.contains(&index) => ordered.push(index),
+                Some(_) => (),
+                None => unmatched.push(name.clone()),
+            }
+        }
+        let mut slots = ordered.clone();
+        slots.sort();
+        let reordered: Vec<ModData> = ordered.iter().map(|index| self.mod_datas[*index].clone()).collect();
+        for (slot, mod_data) in slots.into_iter().zip(reordered) {
+            self.mod_datas[slot] = mod_data;
+        }
+        for (i, data) in self.mod_datas.iter_mut().enumerate() {
+            data.order = i;
+        }
+        self.set_mod_order_config(config);
+        self.log.add_to_log(LogType::Info, format!("Applied load order from {}. Reordered {} of {} listed mod(s).", path.display(), ordered.len(), names.len()));
+        for name in &unmatched {
+            self.log.add_to_log(LogType::Warn, format!("Load order entry {} does not match any installed mod!", name));
+        }
+    }
+
+    fn revert_to_snapshot(&mut self, snapshot: &Snapshot)
+    {
+        let mut reverted: Vec<ModData> = Vec::new();
+        for (name, enabled) in &snapshot.mods {
+            // Snapshots saved by older versions hold mod names instead of folders.
+            let position = self.mod_datas.iter().position(|data| &mod_folder_name(data) == name)
+                .or_else(|| self.mod_datas.iter().position(|data| &data.name == name));
+            if let Some(index) = position {
+                let mut mod_data = self.mod_datas.remove(index);
+                mod_data.enabled = *enabled;
+                reverted.push(mod_data);
+            }
+        }
+        reverted.append(&mut self.mod_datas);
+        for (i, data) in reverted.iter_mut().enumerate() {
+            data.order = i;
+        }
+        self.mod_datas = reverted;
+        let mut config = CONFIG.lock().unwrap();
+        self.set_mod_order_config(&mut config);
+        self.log.add_to_log(LogType::Info, format!("Reverted to setup from {}.", snapshot.timestamp));
+    }
+
+    fn update_mods(&mut self)
+    {
+        // Unsaved changes are newer than config.ini, so only reload it when everything is written.
+        if !self.config_dirty {
+            self.init_config();
+        }
+        self.last_refresh = Some(Instant::now());
+        self.mod_datas.clear();
+        self.mods_path = match &self.custom_mods_path {
+            Some(path) => path.clone(),
+            None => default_mods_path(),
+        };
+        match fs::create_dir_all(&self.mods_path).and_then(|_| fs::read_dir(&self.mods_path))
+        {
+            Ok(_) => {
+                if self.mods_dir_error.take().is_some() {
+                    self.log.add_to_log(LogType::Info, format!("Mods folder {} is available again.", self.mods_path.display()));
+                }
+            }
+            Err(e) => {
+                let error = format!("Could not create or open the mods folder {}! {}\nMake sure the drive is connected and you have permission to write there, or choose a different mods folder.", self.mods_path.display(), e);
+                if self.mods_dir_error.as_ref() != Some(&error) {
+                    self.log.add_to_log(LogType::Error, error.replace('\n', " "));
+                    self.mods_dir_error = Some(error);
+                }
+                return
+            }
+        }
+        let mut config: std::sync::MutexGuard<ConfigState> = CONFIG.lock().unwrap();
+        if !config.config.general.mods_keyed_by_folder {
+            self.migrate_mod_keys(&mut config);
+        }
+        let mod_entries = config.config.mods.clone();
+        let mut config_requires_update = false;
+        for mod_entry in mod_entries {
+            let path = ModData::metadata_file(&Path::join(&self.mods_path, &mod_entry.0));
+            if path.exists()
+            {
+                match ModData::from_file(&path) {
+                    Ok(mut mod_data) => {
+                        if mod_data.name.is_empty() {
+                            self.log.add_to_log(LogType::Warn, format!("The mod ini at path {} doesn't have a name in the desciption section! Ignoring mod.", path.display()));
+                            continue
+                        }
+                        mod_data.path = Path::join(&self.mods_path, &mod_entry.0);
+                        mod_data.load_changelog_file();
+                        mod_data.load_previews();
+                        mod_data.load_packed();
+                        mod_data.load_empty();
+                        mod_data.enabled = mod_entry.1;
+                        mod_data.order = self.mod_datas.len();
+                        mod_data.page_missing = self.missing_pages.contains(&mod_data.name);
+                        self.mod_datas.push(mod_data);
+                    },
+                    Err(ModIniError::MissingDescription) => {
+                        self.log.add_to_log(LogType::Error, format!("The mod ini at path {} doesn't have a description section! Ignoring mod.", path.display()));
+                        config_requires_update = true;
+                        continue
+                    }
+                    Err(ModIniError::Read(_)) => {
+                        self.log.add_to_log(LogType::Error, format!("Ini at path {} does not exist! Ignoring mod.", path.display()));
+                        config_requires_update = true;
+                        continue
+                    }
+                    Err(ModIniError::Json(e)) => {
+                        self.log.add_to_log(LogType::Error, format!("Could not parse {}! Ignoring mod. {}", path.display(), e));
+                        config_requires_update = true;
+                        continue
+                    }
+                }
+            }
+            else {
+                self.log.add_to_log(LogType::Error, format!("Path {} does not exist! Ignoring mod.", path.display()));
+                config_requires_update = true;
+            }
+        }
+        for mod_data in &mut self.mod_datas {
+            init_mod_config(mod_folder_name(mod_data), mod_data, &mut config);
+            load_mod_settings(mod_data, &config.config);
+        }
+        if config_requires_update {
+            self.set_mod_order_config(&mut config)
+        }
+        drop(config);
+        self.detect_duplicate_names();
+        self.detect_incompatibilities();
+        self.warn_unmet_requirements();
+        self.warn_empty_mods();
+        self.resolve_selection();
+        self.write_manifest();
+    }
+
+    /// Older versions keyed per-mod settings by the Name in mod.ini, which differs from the folder for
+    /// mods installed from archives named differently. Moves each such entry to the folder of that mod.
+    fn migrate_mod_keys(&mut self, config: &mut ConfigState)
+    {
+        let folders = mod_folders(&self.mods_path).unwrap_or_default();
+        let keys: Vec<String> = config.config.mods.iter().map(|(name, _)| name.clone()).collect();
+        for key in keys.iter().filter(|key| !folders.contains(key)) {
+            let folder = folders.iter().find(|folder| {
+                config.config.mod_enabled(folder).is_none()
+                    && ModData::from_file(ModData::metadata_file(&Path::join(&self.mods_path, folder))).is_ok_and(|mod_data| &mod_data.name == key)
+            }).cloned();
+            if let Some(folder) = folder {
+                config.config.rename_mod(key, &folder);
+                self.log.add_to_log(LogType::Info, format!("Moved the settings of mod {} to its folder {}.", key, folder));
+            }
+        }
+        config.config.general.mods_keyed_by_folder = true;
+        self.write_config(config);
+    }
+
+    /// Enables scheduled mods inside their dates and disables them outside. Mods without a schedule are unaffected.
+    fn apply_schedules(&mut self)
+    {
+        let today = Local::now().date_naive();
+        let mut changed = false;
+        for mod_data in &mut self.mod_datas {
+            let schedule = match mod_data.schedule {
+                Some(schedule) => schedule,
+                None => continue,
+            };
+            let active = schedule.contains(today.month(), today.day());
+            if mod_data.enabled != active {
+                mod_data.enabled = active;
+                changed = true;
+                let action = match active {
+                    true => "Enabled",
+                    false => "Disabled",
+                };
+                self.log.add_to_log(LogType::Info, format!("{} mod {} for its schedule ({}).", action, mod_data.name, schedule));
+            }
+        }
+        if changed {
+            let mut config = CONFIG.lock().unwrap();
+            self.set_mod_order_config(&mut config);
+        }
+    }
+
+    /// Rebuilds the mod list after changes made in the mod manager. With AutoRefreshSeconds set, it is also
+    /// rebuilt on that interval to pick up mods and metadata edited outside of it. Every refresh reads all
+    /// metadata files, so short intervals are more responsive but cost more disk access and CPU.
+    fn refresh_mods(&mut self, ctx: &egui::Context)
+    {
+        let interval = Duration::from_secs(self.auto_refresh_seconds);
+        let elapsed = self.last_refresh.map(|time| time.elapsed()).unwrap_or(interval);
+        if self.auto_refresh_seconds > 0 && elapsed >= interval {
+            // [Mods] is keyed by folder, so it can be compared with the folders on disk directly.
+            let known_folders: HashSet<String> = CONFIG.lock().unwrap().config.mods.iter().map(|(folder, _)| folder.clone()).collect();
+            let on_disk: Option<HashSet<String>> = mod_folders(&self.mods_path).ok().map(|folders| folders.into_iter().collect());
+            match on_disk {
+                Some(on_disk) if on_disk != known_folders => self.reconcile_mods(),
+                _ => self.update_mods(),
+            }
+            self.refresh_requested = false;
+        }
+        else if std::mem::take(&mut self.refresh_requested) {
+            self.update_mods();
+        }
+        if self.auto_refresh_seconds > 0 {
+            let elapsed = self.last_refresh.map(|time| time.elapsed()).unwrap_or_default();
+            ctx.request_repaint_after(interval.saturating_sub(elapsed));
+        }
+    }
+
+    fn reconcile_mods(&mut self)
+    {
+        let mut on_disk: Vec<String> = match mod_folders(&self.mods_path) {
+            Ok(folders) => folders,
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Could not read mods folder {}! {}", self.mods_path.display(), e));
+                return
+            }
+        };
+        on_disk.sort();
+        let mut config = CONFIG.lock().unwrap();
+        let known: Vec<String> = config.config.mods.iter().map(|(name, _)| name.clone()).collect();
+        let mut missing: Vec<String> = known.iter().filter(|name| !on_disk.contains(name)).cloned().collect();
+        let mut added: Vec<String> = Vec::new();
+        let mut renamed = 0;
+        for folder in on_disk.into_iter().filter(|folder| !known.contains(folder)) {
+            let ini_path = ModData::metadata_file(&Path::join(&self.mods_path, &folder));
+            let mod_data = match ModData::from_file(&ini_path) {
+                Ok(mod_data) => mod_data,
+                Err(_) => {
+                    added.push(folder);
+                    continue
+                }
+            };
+            // A new folder holding the same mod as a folder that disappeared is that folder renamed.
+            let loaded_name = |folder: &String| self.mod_datas.iter()
+                .find(|loaded| &mod_folder_name(loaded) == folder)
+                .map(|loaded| loaded.name.clone());
+            match missing.iter().position(|old_folder| loaded_name(old_folder).as_ref() == Some(&mod_data.name)) {
+                Some(index) => {
+                    // Only the config is keyed by folder, so the mod's own metadata stays as the author wrote it.
+                    let old_name = missing.remove(index);
+                    config.config.rename_mod(&old_name, &folder);
+                    self.log.add_to_log(LogType::Info, format!("Mod folder {} was renamed to {}.", old_name, folder));
+                    renamed += 1;
+                }
+                None => added.push(folder),
+            }
+        }
+        for name in &missing {
+            config.config.remove_mod(name);
+            self.log.add_to_log(LogType::Info, format!("Mod {} was removed from the mods folder.", name));
+        }
+        self.write_config(&mut config);
+        drop(config);
+        self.update_mods();
+        let mut config = CONFIG.lock().unwrap();
+        for folder in &added {
+            self.log.add_to_log(LogType::Info, format!("Found new mod folder {}.", folder));
+            self.init_mod(folder.clone(), &mut config);
+        }
+        self.set_mod_order_config(&mut config);
+        drop(config);
+        match added.len() + missing.len() + renamed {
+            0 => self.log.add_to_log(LogType::Info, "Refreshed mods. Nothing changed on disk.".to_owned()),
+            _ => self.log.add_to_log(LogType::Info, format!("Refreshed mods. {} added, {} removed, {} renamed.", added.len(), missing.len(), renamed)),
+        }
+    }
+
+    fn detect_duplicate_names(&mut self)
+    {
+        let mut folders: HashMap<String, Vec<String>> = HashMap::new();
+        for mod_data in &self.mod_datas {
+            let folder = mod_data.path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            folders.entry(mod_data.name.clone()).or_default().push(folder);
+        }
+        for mod_data in &mut self.mod_datas {
+            mod_data.duplicate_name = folders.get(&mod_data.name).map_or(false, |folders| folders.len() > 1);
+        }
+        for (name, folders) in folders {
+            if folders.len() > 1 && self.duplicate_warnings.insert(name.clone()) {
+                self.log.add_to_log(LogType::Error, format!("Multiple mods are named {}! Give each one a unique name in its mod.ini. Folders: {}", name, folders.join(", ")));
+            }
+        }
+    }
+
+    fn warn_unmet_requirements(&mut self)
+    {
+        for mod_data in &self.mod_datas {
+            if let Some(requirement) = mod_data.unmet_requirement() {
+                if self.requirement_warnings.insert(mod_data.name.clone()) {
+                    self.log.add_to_log(LogType::Warn, format!("Mod {} may not work correctly! {}", mod_data.name, requirement));
+                }
+            }
+        }
+    }
+
+    fn detect_incompatibilities(&mut self)
+    {
+        let pairs = incompatible_pairs(&self.mod_datas);
+        for mod_data in &mut self.mod_datas {
+            mod_data.conflicts = pairs.iter()
+                .filter_map(|(first, second)| match (first == &mod_data.name, second == &mod_data.name) {
+                    (true, _) => Some(second.clone()),
+                    (_, true) => Some(first.clone()),
+                    _ => None,
+                })
+                .collect();
+        }
+        let mut new_pairs = Vec::new();
+        for pair in &pairs {
+            if self.incompatibility_warnings.insert(pair.clone()) {
+                self.log.add_to_log(LogType::Error, format!("Mods {} and {} are incompatible and should not be enabled together!", pair.0, pair.1));
+                new_pairs.push(pair.clone());
+            }
+        }
+        self.incompatibility_warnings.retain(|pair| pairs.contains(pair));
+        if !new_pairs.is_empty() && self.interactive {
+            self.incompatibility_report = Some(incompatibility_report(&new_pairs));
+        }
+    }
+
+    fn warn_empty_mods(&mut self)
+    {
+        for mod_data in &self.mod_datas {
+            if mod_data.empty && self.empty_warnings.insert(mod_data.name.clone()) {
+                self.log.add_to_log(LogType::Warn, format!("Mod {} has no files besides its metadata and will not change anything! Reinstall or remove it.", mod_data.name));
+            }
+        }
+    }
+
+    fn write_manifest(&mut self)
+    {
+        let manifest = match serde_json::to_string_pretty(&self.mod_datas) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Could not serialize mods manifest! {}", e));
+                return
+            }
+        };
+        if manifest == self.last_manifest {
+            return
+        }
+        let mut exe_path = std::env::current_exe().unwrap();
+        exe_path.pop();
+        match fs::write(exe_path.join("mods_manifest.json"), &manifest) {
+            Ok(_) => self.last_manifest = manifest,
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not write mods manifest! {}", e)),
+        }
+    }
+
+    /// Picks up per-mod settings changed in the config without rescanning the mods folder.
+    fn sync_mod_settings(&mut self, config: &ConfigState)
+    {
+        for mod_data in &mut self.mod_datas {
+            load_mod_settings(mod_data, &config.config);
+        }
+        if !self.selected_mod.path.as_os_str().is_empty() {
+            load_mod_settings(&mut self.selected_mod, &config.config);
+        }
+    }
+
+    fn resolve_selection(&mut self)
+    {
+        if self.selected_mod.path.as_os_str().is_empty() {
+            return
+        }
+        match self.mod_datas.iter().find(|data| data.path == self.selected_mod.path) {
+            Some(data) => self.selected_mod = data.clone(),
+            None => {
+                self.log.add_to_log(LogType::Info, format!("Selected mod {} is no longer installed.", self.selected_mod.name));
+                self.selected_mod = ModData::default();
+            }
+        }
+    }
+
+    fn init_log(&mut self) {
+        self.log.init_log();
+        self.log.add_to_log(LogType::Info, "Launched GUILTY GEAR Xrd Mod Manager.".to_owned());
+    }
+
+    fn append_log(&mut self) {
+        self.log.append_log();
+        self.log.add_to_log(LogType::Info, "Another instance of the mod manager was opened!".to_owned());
+    }
+
+    fn compare_with_vanilla(&mut self, mod_data: &ModData) {
+        let cooked_path = Path::join(&self.game_path, "REDGame").join("CookedPCConsole");
+        match helpers::compare_with_vanilla(&mod_data.path, &cooked_path) {
+            Ok(comparison) => {
+                self.log.add_to_log(LogType::Info, format!("Mod {} replaces {} game file(s) and adds {} file(s).", mod_data.name, comparison.replaced.len(), comparison.added.len()));
+                let mut report = format!("Compared {} with {}.\n\nReplaces {} game file(s):", mod_data.name, cooked_path.display(), comparison.replaced.len());
+                for file in &comparison.replaced {
+                    report += &format!("\n  {}", file.display());
+                }
+                report += &format!("\n\nAdds {} file(s):", comparison.added.len());
+                for file in &comparison.added {
+                    report += &format!("\n  {}", file.display());
+                }
+                self.vanilla_report = Some(report);
+            }
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not compare mod {} with game files! {}", mod_data.name, e)),
+        }
+    }
+
+    fn move_mod(&mut self, mod_path: &Path, action: MoveAction) -> bool {
+        let index = match self.mod_datas.iter().position(|mod_data| mod_data.path == mod_path) {
+            Some(index) => index,
+            None => return false,
+        };
+        let last = self.mod_datas.len() - 1;
+        let target = match action {
+            MoveAction::Top => 0,
+            MoveAction::Up => index.saturating_sub(1),
+            MoveAction::Down => (index + 1).min(last),
+            MoveAction::Bottom => last,
+        };
+        if target == index {
+            return false
+        }
+        let mod_data = self.mod_datas.remove(index);
+        self.mod_datas.insert(target, mod_data);
+        for (i, data) in self.mod_datas.iter_mut().enumerate() {
+            data.order = i;
+        }
+        true
+    }
+
+    fn load_templates(&mut self) {
+        self.mod_templates = template::built_in_templates();
+        self.create_template = 0;
+        let mut templates_path = std::env::current_exe().unwrap();
+        templates_path.pop();
+        let templates_path = templates_path.join("templates");
+        if !templates_path.exists() {
+            return
+        }
+        match template::load_user_templates(&templates_path) {
+            Ok(mut templates) => self.mod_templates.append(&mut templates),
+            Err(e) => self.log.add_to_log(LogType::Warn, format!("Could not read mod templates from {}! {}", templates_path.display(), e)),
+        }
+    }
+
+    /// Undoes newly enabled mods when they would take the number of enabled mods past MaxEnabledMods.
+    fn enforce_enabled_limit(&mut self, before: &[(String, bool)]) {
+        let enabled_count = self.mod_datas.iter().filter(|mod_data| mod_data.enabled).count();
+        let enabled_before = before.iter().filter(|(_, enabled)| *enabled).count();
+        if self.max_enabled_mods == 0 || enabled_count <= self.max_enabled_mods || enabled_count <= enabled_before {
+            return
+        }
+        for mod_data in &mut self.mod_datas {
+            let was_enabled = before.iter().any(|(name, enabled)| name == &mod_data.name && *enabled);
+            if mod_data.enabled && !was_enabled {
+                mod_data.enabled = false;
+            }
+        }
+        self.log.add_to_log(LogType::Warn, format!("Cannot enable more than {} mods! Disable a mod first or raise MaxEnabledMods.", self.max_enabled_mods));
+    }
+
+    fn order_snapshot(&self) -> Vec<(String, bool)> {
+        self.mod_datas.iter().map(|mod_data| (mod_folder_name(mod_data), mod_data.enabled)).collect()
+    }
+
+    fn push_order_undo(&mut self, snapshot: Vec<(String, bool)>) {
+        self.order_undo.push(snapshot);
+        if self.order_undo.len() > ORDER_UNDO_DEPTH {
+            self.order_undo.remove(0);
+        }
+        self.order_redo.clear();
+    }
+
+    fn undo_order(&mut self) {
+        match self.order_undo.pop() {
+            Some(snapshot) => {
+                self.order_redo.push(self.order_snapshot());
+                self.restore_order(snapshot);
+                self.log.add_to_log(LogType::Info, "Undid mod order change.".to_owned());
+            }
+            None => self.log.add_to_log(LogType::Info, "Nothing to undo.".to_owned()),
+        }
+    }
+
+    fn redo_order(&mut self) {
+        match self.order_redo.pop() {
+            Some(snapshot) => {
+                self.order_undo.push(self.order_snapshot());
+                self.restore_order(snapshot);
+                self.log.add_to_log(LogType::Info, "Redid mod order change.".to_owned());
+            }
+            None => self.log.add_to_log(LogType::Info, "Nothing to redo.".to_owned()),
+        }
+    }
+
+    /// Restores the order and enabled state from a snapshot. Mods installed or removed since the
+    /// snapshot was taken keep their current state, at the end of the list.
+    fn restore_order(&mut self, snapshot: Vec<(String, bool)>) {
+        let mut restored: Vec<ModData> = Vec::new();
+        for (folder, enabled) in snapshot {
+            if let Some(index) = self.mod_datas.iter().position(|data| mod_folder_name(data) == folder) {
+                let mut mod_data = self.mod_datas.remove(index);
+                mod_data.enabled = enabled;
+                restored.push(mod_data);
+            }
+        }
+        restored.append(&mut self.mod_datas);
+        for (i, data) in restored.iter_mut().enumerate() {
+            data.order = i;
+        }
+        self.mod_datas = restored;
+        self.resolve_selection();
+        let mut config = CONFIG.lock().unwrap();
+        self.set_mod_order_config(&mut config);
+    }
+
+    fn reload_mod(&mut self, mod_path: PathBuf) {
+        let index = match self.mod_datas.iter().position(|mod_data| mod_data.path == mod_path) {
+            Some(index) => index,
+            None => {
+                self.log.add_to_log(LogType::Error, format!("Mod at path {} is not loaded! Could not reload metadata.", mod_path.display()));
+                return
+            }
+        };
+        let path = ModData::metadata_file(&mod_path);
+        match ModData::from_file(&path) {
+            Ok(mut mod_data) => {
+                let old_data = &self.mod_datas[index];
+                if mod_data.name.is_empty() {
+                    mod_data.name = old_data.name.clone();
+                }
+                if mod_data.name != old_data.name {
+                    // Settings are keyed by folder, so the new name can be used right away.
+                    self.log.add_to_log(LogType::Info, format!("The mod ini at path {} changed the mod name from {} to {}.", path.display(), old_data.name, mod_data.name));
+                }
+                mod_data.path = old_data.path.clone();
+                mod_data.enabled = old_data.enabled;
+                mod_data.order = old_data.order;
+                mod_data.conflicts = old_data.conflicts.clone();
+                mod_data.duplicate_name = old_data.duplicate_name;
+                mod_data.page_missing = self.missing_pages.contains(&mod_data.name);
+                mod_data.load_changelog_file();
+                mod_data.load_previews();
+                mod_data.load_packed();
+                mod_data.load_empty();
+                load_mod_settings(&mut mod_data, &CONFIG.lock().unwrap().config);
+                self.log.add_to_log(LogType::Info, format!("Reloaded metadata for mod {}.", mod_data.name));
+                self.mod_datas[index] = mod_data;
+                // The new metadata can change names, incompatibilities and requirements, which involve the other mods too.
+                self.detect_duplicate_names();
+                self.detect_incompatibilities();
+                self.warn_unmet_requirements();
+                self.warn_empty_mods();
+                self.resolve_selection();
+            }
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not reload metadata for mod at path {}! {}", path.display(), e)),
+        }
+    }
+
+    /// Opens the mod's metadata file in the default text editor and offers to reload it afterwards.
+    fn edit_metadata(&mut self, mod_path: PathBuf) {
+        let path = ModData::metadata_file(&mod_path);
+        match open::that(&path) {
+            Ok(_) => {
+                self.log.add_to_log(LogType::Info, format!("Opened {} for editing.", path.display()));
+                self.editing_metadata = Some(mod_path);
+            }
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not open {}! {}", path.display(), e)),
+        }
+    }
+
+    fn init_mod(&mut self, name: String, config: &mut ConfigState)
+    {
+        // The mod was just installed or its files were replaced, so rescan once this frame is done.
+        self.refresh_requested = true;
+        // Mods are told apart by folder, so two folders that share a Name are both loaded.
+        if self.mod_datas.iter().any(|mod_data| mod_folder_name(mod_data) == name) {
+            return
+        }
+
+        let path = ModData::metadata_file(&Path::join(&self.mods_path, &name));
+        if path.exists()
+        {
+            match ModData::from_file(&path) {
+                Ok(mut mod_data) => {
+                    if mod_data.name.is_empty() {
+                        self.log.add_to_log(LogType::Warn, format!("The mod ini at path {} doesn't have a name in the desciption section! Using the folder name instead.", path.display()));
+                        mod_data.name = name.clone();
+                    }
+                    mod_data.path = Path::join(&self.mods_path, &name);
+                    mod_data.load_changelog_file();
+                    mod_data.load_previews();
+                    mod_data.load_packed();
+                    init_mod_config(name.clone(), &mut mod_data, config);
+                    self.write_config(config);
+                    self.mod_datas.push(mod_data);
+                },
+                Err(ModIniError::MissingDescription) => {
+                    self.log.add_to_log(LogType::Warn, format!("The mod ini at path {} doesn't have a description section! Created one automatically.", &path.display()));
+                    self.create_mod_ini(name, config);
+                }
+                Err(ModIniError::Read(_)) => {
+                    self.log.add_to_log(LogType::Warn, format!("No mod ini at path {}! Created one automatically.", &path.display()));
+                    self.create_mod_ini(name, config);
+                }
+                Err(ModIniError::Json(e)) => {
+                    self.log.add_to_log(LogType::Error, format!("Could not parse {}! Ignoring mod. {}", &path.display(), e));
+                }
+            }
+        }
+        else {
+            self.log.add_to_log(LogType::Warn, format!("No mod ini at path {}! Created one automatically.", &path.display()));
+            self.create_mod_ini(name, config);
+        }
+        self.detect_duplicate_names();
+    }
+
+    fn create_mod_ini(&mut self, name: String, config: &mut ConfigState)
+    {
+        let (mut mod_data, inferred) = ModData::infer_from_folder(&name, &Path::join(&self.mods_path, &name));
+        self.log.add_to_log(LogType::Info, format!("Inferred {} for mod {}.", inferred.join(", "), name));
+        if let Err(e) = mod_data.write_data(self.write_mod_json) {
+            self.log.add_to_log(LogType::Error, format!("Could not write mod ini for mod {}! {}", name, e));
+        }
+        mod_data.load_previews();
+        init_mod_config(name, &mut mod_data, config);
+        self.write_config(config);
+        self.mod_datas.push(mod_data);
+    }
+
+    fn extract_any_archive(&mut self, path: &Path, destination: &Path) -> Result<(), String>
+    {
+        archive::extract(path, destination, self.download_settings.temp_dir.as_deref()).map_err(|e| {
+            self.log.add_to_log(LogType::Error, format!("Could not extract archive {}! {}", path.display(), e));
+            e.to_string()
+        })
+    }
+
+    /// Installs a mod archive. Failures are logged, and the reason is also returned for batch summaries.
+    fn install_mod(&mut self, path: PathBuf, config: &mut ConfigState) -> Result<(), String>
+    {
+        let file_stem = match (helpers::split_archive(&path), path.file_stem()) {
+            (Some(split), _) => split.base_name,
+            (None, Some(file_stem)) => file_stem.to_string_lossy().into_owned(),
+            (None, None) => {
+                self.log.add_to_log(LogType::Error, "File has no name!".to_owned());
+                return Err("File has no name!".to_owned())
+            }
+        };
+        let mut name = helpers::sanitize_folder_name(&file_stem);
+        if name != file_stem {
+            self.log.add_to_log(LogType::Info, format!("Renamed mod folder {} to {}.", file_stem, name));
+        }
+        name = match self.resolve_collision(name, &path) {
+            Some(name) => name,
+            None => return Err("Already installed. Skipped because of the name collision setting.".to_owned()),
+        };
+        let mod_path = Path::join(&self.mods_path, &name);
+        if self.interactive && mod_path.exists() {
+            if self.pending_reinstall.is_some() {
+                self.log.add_to_log(LogType::Warn, format!("Mod {} is already installed, and another update is waiting for review! Skipped {}.", name, path.display()));
+                return Err("Already installed, and another update is waiting for review. Install it again afterwards.".to_owned())
+            }
+            let staging = match self.download_settings.create_temp_dir() {
+                Ok(staging) => staging,
+                Err(e) => {
+                    self.log.add_to_log(LogType::Error, format!("Could not create temporary directory! {}", e));
+                    return Err(format!("Could not create temporary directory! {}", e))
+                }
+            };
+            self.extract_any_archive(&path, staging.path())?;
+            // A mod stored compressed is compared by its archive contents and stays compressed,
+            // so keep the new archive, which may live in a download folder that is about to be removed.
+            let mut installed = None;
+            let mut packed = None;
+            if let (Some(old_archive), None) = (archive::packed_archive(&mod_path), helpers::split_archive(&path)) {
+                let prepared = self.download_settings.create_temp_dir().and_then(|unpacked| {
+                    let kept = self.download_settings.create_temp_dir()?;
+                    let archive_path = kept.path().join(path.file_name().unwrap_or_default());
+                    fs::copy(&path, &archive_path)?;
+                    Ok((unpacked, kept, archive_path))
+                });
+                let (unpacked, kept, archive_path) = prepared.map_err(|e| {
+                    self.log.add_to_log(LogType::Error, format!("Could not create temporary directory! {}", e));
+                    format!("Could not create temporary directory! {}", e)
+                })?;
+                self.extract_any_archive(&old_archive, unpacked.path())?;
+                installed = Some(unpacked);
+                packed = Some((kept, archive_path));
+            }
+            let installed_path = installed.as_ref().map(|installed| installed.path().to_path_buf()).unwrap_or_else(|| mod_path.clone());
+            return match helpers::diff_dirs(&installed_path, staging.path()) {
+                Ok(diff) => {
+                    self.log.add_to_log(LogType::Info, format!("Mod {} is already installed. Review the changes before updating.", name));
+                    self.pending_reinstall = Some(PendingReinstall { name, mod_path, staging, packed, diff });
+                    Ok(())
+                }
+                Err(e) => {
+                    self.log.add_to_log(LogType::Error, format!("Could not compare mod {} with the new version! {}", name, e));
+                    Err(format!("Could not compare with the installed version! {}", e))
+                }
+            }
+        }
+        match (self.compressed_storage, helpers::split_archive(&path)) {
+            (true, None) => self.install_packed(&name, &path, &mod_path)?,
+            (true, Some(_)) => {
+                self.log.add_to_log(LogType::Info, format!("Split archives can't be stored compressed. Extracting mod {} instead.", name));
+                self.extract_any_archive(&path, &mod_path)?
+            }
+            (false, _) => self.extract_any_archive(&path, &mod_path)?,
+        }
+        match helpers::read_collection(&mod_path) {
+            Some(collection) => self.install_collection(collection, &mod_path, config),
+            None => self.init_mod(name, config),
+        }
+        Ok(())
+    }
+
+    /// Stores a mod compressed. The archive is copied into the mod folder next to its metadata, changelog
+    /// and previews, and the rest is only extracted when applying. Collections are always extracted.
+    fn install_packed(&mut self, name: &str, path: &Path, mod_path: &Path) -> Result<(), String>
+    {
+        let staging = self.download_settings.create_temp_dir().map_err(|e| {
+            self.log.add_to_log(LogType::Error, format!("Could not create temporary directory! {}", e));
+            format!("Could not create temporary directory! {}", e)
+        })?;
+        self.extract_any_archive(path, staging.path())?;
+        if helpers::read_collection(staging.path()).is_some() {
+            return helpers::copy_recursively(staging.path(), mod_path).map_err(|e| {
+                self.log.add_to_log(LogType::Error, format!("Could not copy collection {}! {}", name, e));
+                e.to_string()
+            })
+        }
+        let extension = path.extension().map(|extension| extension.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let result = fs::create_dir_all(mod_path).and_then(|_| {
+            if let Some(old) = archive::packed_archive(mod_path) {
+                fs::remove_file(old)?;
+            }
+            for entry in fs::read_dir(staging.path())? {
+                let entry = entry?;
+                let file_name = entry.file_name().to_string_lossy().to_lowercase();
+                let is_image = [".png", ".jpg", ".jpeg"].iter().any(|extension| file_name.ends_with(extension));
+                match file_name.as_str() {
+                    "previews" => helpers::copy_recursively(entry.path(), mod_path.join(entry.file_name()))?,
+                    "mod.ini" | "mod.json" | "changelog.txt" | "changelog.md" => { fs::copy(entry.path(), mod_path.join(entry.file_name()))?; }
+                    _ if is_image => { fs::copy(entry.path(), mod_path.join(entry.file_name()))?; }
+                    _ => (),
+                }
+            }
+            if !ModData::metadata_file(mod_path).exists() {
+                // Infer the metadata from the extracted files, since the mod folder only holds the archive.
+                let (mut mod_data, inferred) = ModData::infer_from_folder(name, staging.path());
+                self.log.add_to_log(LogType::Info, format!("Inferred {} for mod {}.", inferred.join(", "), name));
+                mod_data.path = mod_path.to_path_buf();
+                mod_data.write_data(self.write_mod_json)?;
+            }
+            fs::copy(path, mod_path.join(format!("{}.{}", archive::PACKED_NAME, extension)))
+        });
+        match result {
+            Ok(size) => {
+                self.log.add_to_log(LogType::Info, format!("Stored mod {} compressed ({}).", name, helpers::format_size(size)));
+                Ok(())
+            }
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Could not store mod {} compressed! {}", name, e));
+                Err(e.to_string())
+            }
+        }
+    }
+
+    fn install_dropped_files(&mut self, ctx: &egui::Context)
+    {
+        let paths: Vec<PathBuf> = ctx.input(|i| i.raw.dropped_files.iter().filter_map(|file| file.path.clone()).collect());
+        if paths.is_empty() {
+            return
+        }
+        let mut config = CONFIG.lock().unwrap();
+        self.install_batch(paths, &mut config);
+        self.set_mod_order_config(&mut config);
+    }
+
+    /// Installs several archives and shows a summary of which ones failed and why.
+    fn install_batch(&mut self, paths: Vec<PathBuf>, config: &mut ConfigState)
+    {
+        if let [path] = paths.as_slice() {
+            self.install_mod(path.clone(), config).unwrap_or_default();
+            return
+        }
+        self.log.add_to_log(LogType::Info, format!("Installing {} archives...", paths.len()));
+        let mut results: Vec<(PathBuf, Result<(), String>)> = Vec::new();
+        for path in paths {
+            let result = self.install_mod(path.clone(), config);
+            results.push((path, result));
+        }
+        let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+        self.log.add_to_log(LogType::Info, format!("Installed {} of {} archives.", results.len() - failed, results.len()));
+        self.install_report = Some(results);
+    }
+
+    fn resolve_collision(&mut self, name: String, source: &Path) -> Option<String>
+    {
+        if !Path::join(&self.mods_path, &name).exists() {
+            return Some(name)
+        }
+        match self.collision_policy {
+            CollisionPolicy::Overwrite => Some(name),
+            CollisionPolicy::Rename => {
+                let new_name = helpers::unique_dir_name(&self.mods_path, &name);
+                self.log.add_to_log(LogType::Info, format!("Mod {} is already installed. Installing as {} instead.", name, new_name));
+                Some(new_name)
+            }
+            CollisionPolicy::Skip => {
+                self.log.add_to_log(LogType::Warn, format!("Mod {} is already installed. Skipped installing {}.", name, source.display()));
+                None
+            }
+        }
+    }
+
+    fn install_collection(&mut self, collection: helpers::Collection, collection_path: &Path, config: &mut ConfigState)
+    {
+        self.log.add_to_log(LogType::Info, format!("Installing collection {} with {} mod(s)...", collection.name, collection.mods.len()));
+        let mut imported = 0;
+        for folder in collection.mods {
+            // collection.ini is untrusted, so only accept plain folder names inside the collection.
+            let mut components = Path::new(&folder).components();
+            if !matches!((components.next(), components.next()), (Some(Component::Normal(_)), None)) {
+                self.log.add_to_log(LogType::Warn, format!("Collection {} lists mod {}, which is not a folder in the collection! Skipped it.", collection.name, folder));
+                continue
+            }
+            let source = collection_path.join(&folder);
+            if !ModData::metadata_file(&source).is_file() {
+                self.log.add_to_log(LogType::Warn, format!("Collection {} lists mod {}, but it has no mod.ini! Skipped it.", collection.name, folder));
+                continue
+            }
+            let name = match self.resolve_collision(helpers::sanitize_folder_name(&folder), &source) {
+                Some(name) => name,
+                None => continue,
+            };
+            let destination = Path::join(&self.mods_path, &name);
+            // Compare canonical paths, since folder names are case-insensitive on Windows.
+            if destination.exists() && fs::canonicalize(&destination).ok() == fs::canonicalize(collection_path).ok() {
+                self.log.add_to_log(LogType::Warn, format!("Mod {} from collection {} would replace the collection folder itself! Skipped it.", folder, collection.name));
+                continue
+            }
+            let moved = match destination.exists() {
+                true => fs::remove_dir_all(&destination).and_then(|_| fs::rename(&source, &destination)),
+                false => fs::rename(&source, &destination),
+            };
+            match moved {
+                Ok(_) => {
+                    self.init_mod(name, config);
+                    imported += 1;
+                }
+                Err(e) => self.log.add_to_log(LogType::Error, format!("Could not install mod {} from collection {}! {}", folder, collection.name, e)),
+            }
+        }
+        if let Err(e) = fs::remove_dir_all(collection_path) {
+            self.log.add_to_log(LogType::Warn, format!("Could not clean up collection folder {}! {}", collection_path.display(), e));
+        }
+        self.log.add_to_log(LogType::Info, format!("Imported {} mod(s) from collection {}.", imported, collection.name));
+    }
+
+    fn restore_backup(&mut self, pending: PendingRestore)
+    {
+        let folders: Vec<String> = pending.entries.into_iter()
+            .filter(|(_, selected)| *selected)
+            .map(|(entry, _)| entry.folder)
+            .collect();
+        if folders.is_empty() {
+            return
+        }
+        match backup::restore_backup(&pending.path, &self.mods_path, &folders) {
+            Ok(files) => {
+                self.log.add_to_log(LogType::Info, format!("Restored {} mod(s) ({} files) from {}.", folders.len(), helpers::format_count(files), pending.path.display()));
+                let mut config = CONFIG.lock().unwrap();
+                for folder in folders {
+                    self.init_mod(folder, &mut config);
+                }
+                self.set_mod_order_config(&mut config);
+            }
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not restore backup {}! {}", pending.path.display(), e)),
+        }
+    }
+
+    fn finish_reinstall(&mut self, pending: PendingReinstall, config: &mut ConfigState)
+    {
+        let updated = match &pending.packed {
+            Some((_, archive_path)) => fs::remove_dir_all(&pending.mod_path)
+                .map_err(|e| e.to_string())
+                .and_then(|_| self.install_packed(&pending.name, archive_path, &pending.mod_path)),
+            None => fs::remove_dir_all(&pending.mod_path)
+                .and_then(|_| helpers::copy_recursively(pending.staging.path(), &pending.mod_path))
+                .map_err(|e| e.to_string()),
+        };
+        match updated {
+            Ok(_) => {
+                self.log.add_to_log(LogType::Info, format!("Updated mod {}! {} file(s) added, {} changed, {} removed.",
+                    pending.name, pending.diff.added.len(), pending.diff.changed.len(), pending.diff.removed.len()));
+                self.init_mod(pending.name, config);
+            }
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not update mod {}! {}", pending.name, e)),
+        }
+    }
+
+    fn poll_download(&mut self) -> bool
+    {
+        match &self.download_task {
+            Some(task) if task.is_finished() => (),
+            _ => return false,
+        }
+        let task = self.download_task.take().unwrap();
+        let url = task.url.clone();
+        match task.join() {
+            Ok((path, _tempdir)) => {
+                let mut config = CONFIG.lock().unwrap();
+                self.install_mod(path, &mut config).unwrap_or_default();
+                self.set_mod_order_config(&mut config);
+                true
+            }
+            Err(download::Error(download::ErrorKind::TimedOut(_), _)) => {
+                self.log.add_to_log(LogType::Error, format!("Download from {} timed out! Install it from URL to try again.", url));
+                self.url_input = url;
+                false
+            }
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Could not download mod! {}", e));
+                false
+            }
+        }
+    }
+
+    fn poll_page_check(&mut self)
+    {
+        match &self.page_check_task {
+            Some(task) if task.is_finished() => (),
+            _ => return,
+        }
+        let task = self.page_check_task.take().unwrap();
+        match task.join() {
+            Ok(results) => {
+                self.missing_pages.clear();
+                for result in &results {
+                    match &result.status {
+                        Ok(status) if result.is_taken_down() => {
+                            self.log.add_to_log(LogType::Warn, format!("The page of mod {} returned {}! It was likely taken down. ({})", result.name, status, result.page));
+                            self.missing_pages.insert(result.name.clone());
                         }
+                        Ok(_) => (),
+                        Err(e) => self.log.add_to_log(LogType::Warn, format!("Could not check the page of mod {}! {}", result.name, e)),
                     }
-                },
-                Err(_) => {
-                    mod_data.name = name.clone();
-                    mod_data.path = Path::join(&self.mods_path, &name);
-                    mod_data.write_data().unwrap_or_default();
-                    init_mod_config(name, &mut mod_data, config);
-                    self.write_config(config);
-                    self.mod_datas.push(mod_data);
-                    self.log.add_to_log(LogType::Warn, format!("No mod ini at path {}! Created one automatically.", &path.display()));
                 }
+                for mod_data in &mut self.mod_datas {
+                    mod_data.page_missing = self.missing_pages.contains(&mod_data.name);
+                }
+                self.selected_mod.page_missing = self.missing_pages.contains(&self.selected_mod.name);
+                let missing: Vec<&str> = results.iter().filter(|result| result.is_taken_down()).map(|result| result.name.as_str()).collect();
+                match missing.is_empty() {
+                    true => self.log.add_to_log(LogType::Info, format!("Checked {} mod pages. No takedowns found.", results.len())),
+                    false => self.log.add_to_log(LogType::Warn, format!("Checked {} mod pages. Likely taken down: {}", results.len(), missing.join(", "))),
+                }
+            }
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not check mod pages! {}", e)),
+        }
+    }
+
+    fn update_tray(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame)
+    {
+        if !self.close_to_tray {
+            self.tray = None;
+            return
+        }
+        if self.tray.is_none() {
+            match Tray::new(&load_icon()) {
+                Ok(tray) => self.tray = Some(tray),
+                Err(e) => {
+                    self.log.add_to_log(LogType::Error, format!("Could not create tray icon! {}", e));
+                    self.close_to_tray = false;
+                    return
+                }
+            }
+        }
+        if self.hide_to_tray {
+            self.hide_to_tray = false;
+            frame.set_visible(false);
+            self.log.add_to_log(LogType::Info, "Minimized to tray.".to_owned());
+        }
+        let action = self.tray.as_ref().and_then(|tray| tray.poll());
+        match action {
+            Some(TrayAction::Show) => {
+                frame.set_visible(true);
+                frame.focus();
+            }
+            Some(TrayAction::Launch) => self.setup_mods_and_play(),
+            Some(TrayAction::Quit) => {
+                self.quit_requested = true;
+                frame.close();
+            }
+            None => (),
+        }
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+
+    fn apply_theme(&mut self, ctx: &egui::Context)
+    {
+        let dark = match self.theme {
+            Theme::Light => false,
+            Theme::Dark => true,
+            Theme::System => {
+                let due = match self.last_theme_check {
+                    Some(checked) => checked.elapsed() >= Duration::from_secs(THEME_CHECK_SECONDS),
+                    None => true,
+                };
+                if due {
+                    self.system_dark = dark_light::detect() != dark_light::Mode::Light;
+                    self.last_theme_check = Some(Instant::now());
+                }
+                ctx.request_repaint_after(Duration::from_secs(THEME_CHECK_SECONDS));
+                self.system_dark
+            }
+        };
+        if ctx.style().visuals.dark_mode != dark || self.applied_high_contrast != Some(self.high_contrast) {
+            let mut visuals = match dark {
+                true => egui::Visuals::dark(),
+                false => egui::Visuals::light(),
+            };
+            if self.high_contrast {
+                let (background, text) = match dark {
+                    true => (Color32::BLACK, Color32::WHITE),
+                    false => (Color32::WHITE, Color32::BLACK),
+                };
+                visuals.override_text_color = Some(text);
+                visuals.panel_fill = background;
+                visuals.window_fill = background;
+                visuals.extreme_bg_color = background;
+                visuals.widgets.noninteractive.bg_stroke.color = text;
+                visuals.widgets.inactive.fg_stroke.color = text;
+            }
+            ctx.set_visuals(visuals);
+            self.applied_high_contrast = Some(self.high_contrast);
+        }
+    }
+
+    fn apply_ui_scale(&mut self, ctx: &egui::Context, frame: &eframe::Frame)
+    {
+        let target = frame.info().native_pixels_per_point.unwrap_or(1.) * self.ui_scale;
+        if (ctx.pixels_per_point() - target).abs() > 0.001 {
+            ctx.set_pixels_per_point(target);
+        }
+    }
+
+    fn save_accessibility(&mut self)
+    {
+        let mut config = CONFIG.lock().unwrap();
+        config.config.window.ui_scale = Some(self.ui_scale);
+        config.config.window.high_contrast = self.high_contrast;
+        self.write_config(&mut config);
+    }
+
+    fn save_console_colors(&mut self)
+    {
+        let [info, warn, error] = self.console_color_overrides.map(|color| color.map(hex_color));
+        let mut config = CONFIG.lock().unwrap();
+        config.config.console.info_color = info;
+        config.config.console.warn_color = warn;
+        config.config.console.error_color = error;
+        self.write_config(&mut config);
+    }
+
+    fn preview_texture(&mut self, ctx: &egui::Context, path: &Path) -> Option<egui::TextureHandle>
+    {
+        if let Some(texture) = self.preview_textures.get(path) {
+            return texture.clone()
+        }
+        let texture = match image::open(path) {
+            Ok(image) => {
+                let image = image.into_rgba8();
+                let size = [image.width() as usize, image.height() as usize];
+                let color_image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_flat_samples().as_slice());
+                Some(ctx.load_texture(path.display().to_string(), color_image, Default::default()))
+            }
+            Err(e) => {
+                self.log.add_to_log(LogType::Warn, format!("Could not load preview image {}! {}", path.display(), e));
+                None
+            }
+        };
+        self.preview_textures.insert(path.to_path_buf(), texture.clone());
+        texture
+    }
+
+    /// Returns the mod's icon as a thumbnail, or the mod manager's icon if it has none or it can't be read.
+    /// Thumbnails are cached by path and modification time, so editing the icon file reloads it.
+    fn mod_icon(&mut self, ctx: &egui::Context, icon_path: Option<PathBuf>) -> egui::TextureHandle
+    {
+        if let Some(path) = icon_path {
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+            let key = (path, modified);
+            let texture = match self.icon_textures.get(&key) {
+                Some(texture) => texture.clone(),
+                None => {
+                    let texture = match image::open(&key.0) {
+                        Ok(image) => {
+                            let image = image.thumbnail(MOD_ICON_SIZE * 2, MOD_ICON_SIZE * 2).into_rgba8();
+                            let size = [image.width() as usize, image.height() as usize];
+                            let color_image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_flat_samples().as_slice());
+                            Some(ctx.load_texture(key.0.display().to_string(), color_image, Default::default()))
+                        }
+                        Err(e) => {
+                            self.log.add_to_log(LogType::Warn, format!("Could not load mod icon {}! {}", key.0.display(), e));
+                            None
+                        }
+                    };
+                    self.icon_textures.insert(key, texture.clone());
+                    texture
+                }
+            };
+            if let Some(texture) = texture {
+                return texture
+            }
+        }
+        self.default_icon.get_or_insert_with(|| {
+            let icon = load_icon();
+            let color_image = egui::ColorImage::from_rgba_unmultiplied([icon.width as usize, icon.height as usize], &icon.rgba);
+            ctx.load_texture("default_mod_icon", color_image, Default::default())
+        }).clone()
+    }
+
+    fn file_menu(&mut self, ui: &mut Ui, config: &mut ConfigState)
+    {
+        if ui.button("Install Mod").clicked() {
+            if let Some(paths) = rfd::FileDialog::new()
+            .add_filter("All supported archives", &["zip", "rar", "7z", "001"])
+            .add_filter("ZIP archive", &["zip"])
+            .add_filter("7Z archive", &["7z"])
+            .add_filter("RAR archive", &["rar"])
+            .add_filter("Split 7Z archive", &["001"])
+            .pick_files() {
+                self.install_batch(paths, config)
+            };
+            ui.close_menu();
+        }
+        let mut window = WINDOW.lock().unwrap();
+        if ui.button("Install from URL").clicked() {
+            window.url_open = true;
+            ui.close_menu();
+        }
+        if ui.button("Create Mod").clicked() {
+            self.load_templates();
+            window.create_open = true;
+            ui.close_menu();
+        }
+        if ui.button("Back Up Mods").clicked() {
+            if let Some(path) = rfd::FileDialog::new()
+            .add_filter("ZIP archive", &["zip"])
+            .set_file_name(&format!("Mods-backup-{}.zip", Local::now().format("%Y%m%d-%H%M%S")))
+            .save_file() {
+                match backup::create_backup(&self.mods_path, &path) {
+                    Ok(files) => self.log.add_to_log(LogType::Info, format!("Backed up {} files from the mods folder to {}.", helpers::format_count(files), path.display())),
+                    Err(e) => self.log.add_to_log(LogType::Error, format!("Could not back up the mods folder to {}! {}", path.display(), e)),
+                }
+            }
+            ui.close_menu()
+        }
+        if ui.button("Restore Mods Backup").clicked() {
+            if let Some(path) = rfd::FileDialog::new()
+            .add_filter("ZIP archive", &["zip"])
+            .pick_file() {
+                match backup::list_backup(&path) {
+                    Ok(entries) if entries.is_empty() => self.log.add_to_log(LogType::Warn, format!("The backup {} doesn't contain any mod folders!", path.display())),
+                    Ok(entries) => self.pending_restore = Some(PendingRestore {
+                        path,
+                        entries: entries.into_iter().map(|entry| (entry, true)).collect(),
+                    }),
+                    Err(e) => self.log.add_to_log(LogType::Error, format!("Could not read backup {}! {}", path.display(), e)),
+                }
+            }
+            ui.close_menu()
+        }
+        if ui.button("Export Mod List").on_hover_text("Save the enabled mods as a text file with one folder name per line, for use with other tools.").clicked() {
+            if let Some(path) = rfd::FileDialog::new()
+            .add_filter("Text file", &["txt"])
+            .set_file_name("mods.txt")
+            .save_file() {
+                self.export_mod_list(&path);
+            }
+            ui.close_menu()
+        }
+        if ui.button("Import Mod List").on_hover_text("Enable the mods named in a text file, in its order, and disable the rest.").clicked() {
+            if let Some(path) = rfd::FileDialog::new()
+            .add_filter("Text file", &["txt"])
+            .pick_file() {
+                self.import_mod_list(&path, config);
+            }
+            ui.close_menu()
+        }
+        if ui.button("Apply Load Order").on_hover_text("Reorder your installed mods to match a mod list file. Enabled state is not changed.").clicked() {
+            if let Some(path) = rfd::FileDialog::new()
+            .add_filter("Text file", &["txt"])
+            .pick_file() {
+                self.apply_load_order(&path, config);
+            }
+            ui.close_menu()
+        }
+        if ui.button("Locate Mod").clicked() {
+            if let Some(path) = rfd::FileDialog::new()
+            .add_filter("INI file", &["ini"])
+            .pick_file() {
+                let mut name = path.clone();
+                name.pop();
+                self.init_mod(name.display().to_string(), config)
+            }
+            ui.close_menu()
+        }
+    }
+
+    fn settings_menu(&mut self, ui: &mut Ui)
+    {
+        if ui.checkbox(&mut self.console_visible, "Show Console (F12)").changed() {
+            self.save_console_layout();
+            ui.close_menu();
+        }
+        if ui.checkbox(&mut self.table_view, "Table View").changed() {
+            ui.close_menu();
+        }
+        if ui.checkbox(&mut self.grouped_view, "Group by Category").changed() {
+            ui.close_menu();
+        }
+        if ui.checkbox(&mut self.close_to_tray, "Close to Tray").changed() {
+            let mut config = CONFIG.lock().unwrap();
+            config.config.general.close_to_tray = self.close_to_tray;
+            self.write_config(&mut config);
+            ui.close_menu();
+        }
+        let scale_response = ui.add(egui::Slider::new(&mut self.ui_scale, 0.5..=3.).step_by(0.05).text("UI Scale"));
+        self.ui_scale_dragging = scale_response.dragged();
+        if scale_response.drag_released() || (scale_response.changed() && !scale_response.dragged()) {
+            self.save_accessibility();
+        }
+        if ui.checkbox(&mut self.high_contrast, "High Contrast").changed() {
+            self.save_accessibility();
+            ui.close_menu();
+        }
+        if ui.button("Console Colors...").clicked() {
+            WINDOW.lock().unwrap().console_colors_open = true;
+            ui.close_menu();
+        }
+        if ui.checkbox(&mut self.verify_apply, "Verify Files After Applying").changed() {
+            let mut config = CONFIG.lock().unwrap();
+            config.config.general.verify_apply = self.verify_apply;
+            self.write_config(&mut config);
+            ui.close_menu();
+        }
+        if ui.checkbox(&mut self.hardlink_duplicates, "Hardlink Duplicate Files").on_hover_text("When several enabled mods contain the same large file, store it only once in the game folder.").changed() {
+            let mut config = CONFIG.lock().unwrap();
+            config.config.general.hardlink_duplicates = self.hardlink_duplicates;
+            self.write_config(&mut config);
+            ui.close_menu();
+        }
+        if ui.checkbox(&mut self.write_mod_json, "Write New Mod Metadata as JSON").on_hover_text("Mods without metadata get a mod.json instead of a mod.ini. Existing files keep their format.").changed() {
+            let mut config = CONFIG.lock().unwrap();
+            config.config.general.write_mod_json = self.write_mod_json;
+            self.write_config(&mut config);
+            ui.close_menu();
+        }
+        if ui.checkbox(&mut self.compressed_storage, "Store New Mods Compressed")
+            .on_hover_text("Keep newly installed mods as archives and extract them only when applying. Saves disk space, but applying takes longer and uses temporary space. Already installed mods are unaffected.")
+            .changed() {
+            let mut config = CONFIG.lock().unwrap();
+            config.config.general.compressed_storage = self.compressed_storage;
+            self.write_config(&mut config);
+            ui.close_menu();
+        }
+        if ui.checkbox(&mut self.preserve_unmanaged_mods, "Keep Manually Installed Mods").on_hover_text("Only remove mod folders in the game directory that were applied by the mod manager.").changed() {
+            let mut config = CONFIG.lock().unwrap();
+            config.config.general.preserve_unmanaged_mods = self.preserve_unmanaged_mods;
+            self.write_config(&mut config);
+            ui.close_menu();
+        }
+        if ui.checkbox(&mut self.block_incompatible_mods, "Block Incompatible Mods").on_hover_text("Refuse to apply mods while two enabled mods declare each other incompatible. When off, only a warning is logged.").changed() {
+            let mut config = CONFIG.lock().unwrap();
+            config.config.general.block_incompatible_mods = self.block_incompatible_mods;
+            self.write_config(&mut config);
+            ui.close_menu();
+        }
+        if ui.checkbox(&mut self.register_protocol, "Register xrdmodman: Links").on_hover_text("Let one-click install links from websites open the mod manager. Turning this off removes the handler from the registry.").changed() {
+            let result = match self.register_protocol {
+                true => self.init_registry(),
+                false => self.remove_registry(),
+            };
+            match (result, self.register_protocol) {
+                (Ok(_), true) => self.log.add_to_log(LogType::Info, "Registered xrdmodman: link handler!".to_owned()),
+                (Ok(_), false) => self.log.add_to_log(LogType::Info, "Removed xrdmodman: link handler from registry!".to_owned()),
+                (Err(e), _) => {
+                    self.log.add_to_log(LogType::Error, format!("Failed to change registry! {}", e));
+                    self.offer_elevation(&e, "The mod manager could not change the xrdmodman: link handler in the registry.");
+                }
+            }
+            let mut config = CONFIG.lock().unwrap();
+            config.config.general.register_protocol = self.register_protocol;
+            self.write_config(&mut config);
+            ui.close_menu();
+        }
+        ui.menu_button("Launch Method", |ui| {
+            let mut launch_method = self.launch_method;
+            ui.radio_value(&mut launch_method, LaunchMethod::Steam, "Steam");
+            ui.radio_value(&mut launch_method, LaunchMethod::Direct, "Direct (run the game exe)");
+            if launch_method != self.launch_method {
+                self.launch_method = launch_method;
+                let mut config = CONFIG.lock().unwrap();
+                config.config.general.launch_method = launch_method;
+                self.write_config(&mut config);
+                ui.close_menu();
+            }
+        });
+        ui.menu_button("On Name Collision", |ui| {
+            let mut collision_policy = self.collision_policy;
+            ui.radio_value(&mut collision_policy, CollisionPolicy::Overwrite, "Overwrite the installed mod");
+            ui.radio_value(&mut collision_policy, CollisionPolicy::Rename, "Install under a new name");
+            ui.radio_value(&mut collision_policy, CollisionPolicy::Skip, "Skip the install");
+            if collision_policy != self.collision_policy {
+                self.collision_policy = collision_policy;
+                let mut config = CONFIG.lock().unwrap();
+                config.config.general.on_name_collision = collision_policy;
+                self.write_config(&mut config);
+                ui.close_menu();
+            }
+        });
+        if ui.button("Change Mods Folder").clicked() {
+            if let Some(path) = rfd::FileDialog::new()
+            .set_directory(&self.mods_path)
+            .pick_folder() {
+                match helpers::ensure_writable_dir(&path) {
+                    Ok(_) => self.pending_mods_path = Some(path),
+                    Err(e) => self.log.add_to_log(LogType::Error, format!("Mods folder {} is not writable! {}", path.display(), e)),
+                }
+            }
+            ui.close_menu();
+        }
+        if self.custom_mods_path.is_some() && ui.button("Reset Mods Folder").clicked() {
+            self.pending_mods_path = Some(default_mods_path());
+            ui.close_menu();
+        }
+    }
+
+    fn save_console_layout(&mut self)
+    {
+        let mut config = CONFIG.lock().unwrap();
+        config.config.window.console_visible = self.console_visible;
+        config.config.window.console_height = self.console_height;
+        // The layout does not affect the mod list, so skip the refresh write_config would request
+        // and leave the write to flush_config_debounced, which batches the writes while dragging.
+        self.config_dirty = true;
+    }
+
+    fn set_mods_path(&mut self, path: PathBuf, move_mods: bool)
+    {
+        if path == self.mods_path {
+            return
+        }
+        if move_mods {
+            // Moving the mods into a folder inside themselves would copy that folder into itself.
+            let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+            if path.starts_with(&self.mods_path) || canonical(&path).starts_with(canonical(&self.mods_path)) {
+                self.log.add_to_log(LogType::Error, format!("Could not move mods to {}, which is inside the current mods folder {}! Choose a folder outside of it.", path.display(), self.mods_path.display()));
+                return
+            }
+            match fs::read_dir(&self.mods_path) {
+                Ok(entries) => {
+                    for entry in entries.flatten() {
+                        if canonical(&entry.path()) == canonical(&path) {
+                            continue
+                        }
+                        let destination = path.join(entry.file_name());
+                        match helpers::move_path(entry.path(), &destination) {
+                            Ok(_) => self.log.add_to_log(LogType::Info, format!("Moved {} to {}.", entry.path().display(), destination.display())),
+                            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not move {}! {}", entry.path().display(), e)),
+                        }
+                    }
+                }
+                Err(e) => self.log.add_to_log(LogType::Error, format!("Could not read mods folder {}! {}", self.mods_path.display(), e)),
+            }
+        }
+        let mut config = CONFIG.lock().unwrap();
+        match path == default_mods_path() {
+            true => {
+                config.config.general.mods_path = None;
+                self.custom_mods_path = None;
+            }
+            false => {
+                config.config.general.mods_path = Some(path.display().to_string());
+                self.custom_mods_path = Some(path.clone());
+            }
+        }
+        self.write_config(&mut config);
+        self.refresh_requested = true;
+        self.log.add_to_log(LogType::Info, format!("Mods folder changed to {}.", path.display()));
+    }
+
+    fn tools_menu(&mut self, ui: &mut Ui)
+    {
+        if ui.button("Open Steam Properties").clicked() {
+            match open::that(format!("steam://gameproperties/{}", self.app_id)) {
+                Ok(_) => self.log.add_to_log(LogType::Info, "Opened Steam properties for Guilty Gear Xrd Rev 2.".to_owned()),
+                Err(e) => self.log.add_to_log(LogType::Error, format!("Could not open Steam properties! {}", e)),
+            }
+            ui.close_menu();
+        }
+        if ui.button("Launch Options").clicked() {
+            WINDOW.lock().unwrap().launch_options_open = true;
+            ui.close_menu();
+        }
+        ui.menu_button("Sandbox", |ui| {
+            if ui.button("Test Apply in Sandbox").on_hover_text("Apply the enabled mods to a separate folder instead of the game, without launching it.").clicked() {
+                self.sandbox_apply();
+                ui.close_menu();
+            }
+            if ui.button("Change Sandbox Folder").clicked() {
+                if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                    let mut config = CONFIG.lock().unwrap();
+                    config.config.general.sandbox_path = Some(path.display().to_string());
+                    self.write_config(&mut config);
+                    drop(config);
+                    self.log.add_to_log(LogType::Info, format!("Sandbox folder set to {}.", path.display()));
+                }
+                ui.close_menu();
+            }
+            if ui.button("Open Sandbox Folder").clicked() {
+                let sandbox_path = self.sandbox_path();
+                if let Err(e) = fs::create_dir_all(&sandbox_path).and_then(|_| open::that(&sandbox_path)) {
+                    self.log.add_to_log(LogType::Error, format!("Could not open sandbox folder {}! {}", sandbox_path.display(), e));
+                }
+                ui.close_menu();
+            }
+        });
+        if ui.button("Clean Uninstall Check").on_hover_text("Check that no mods or script packages are left in the game, e.g. before playing online without mods.").clicked() {
+            self.check_clean_install();
+            ui.close_menu();
+        }
+        if ui.button("Open Game Logs").clicked() {
+            self.open_game_logs();
+            ui.close_menu();
+        }
+        if ui.add_enabled(self.page_check_task.is_none(), egui::Button::new("Check mod pages")).clicked() {
+            let pages: Vec<(String, String)> = self.mod_datas.iter()
+                .filter(|mod_data| !mod_data.page.is_empty())
+                .map(|mod_data| (mod_data.name.clone(), mod_data.page.clone()))
+                .collect();
+            self.log.add_to_log(LogType::Info, format!("Checking pages of {} mods...", pages.len()));
+            self.page_check_task = Some(PageCheckTask::start(pages, self.download_settings.clone()));
+            ui.close_menu();
+        }
+        ui.menu_button("Revert to previous setup", |ui| {
+            if self.history.is_empty() {
+                ui.label("No setups have been applied yet.");
+            }
+            let mut selected: Option<Snapshot> = None;
+            for snapshot in &self.history {
+                if ui.button(format!("{} ({} of {} enabled)", snapshot.timestamp, snapshot.enabled_count(), snapshot.mods.len())).clicked() {
+                    selected = Some(snapshot.clone());
+                }
+            }
+            if let Some(snapshot) = selected {
+                self.revert_to_snapshot(&snapshot);
+                ui.close_menu();
+            }
+        });
+    }
+
+    fn help_menu(&mut self, ui: &mut Ui)
+    {
+        if ui.button("Diagnostics").clicked() {
+            self.run_diagnostics();
+            ui.close_menu();
+        }
+        if ui.button("Copy Diagnostics to Clipboard").on_hover_text("Copy system details and recent log lines for a bug report.").clicked() {
+            let summary = self.diagnostics_summary();
+            ui.output_mut(|o| o.copied_text = summary);
+            self.log.add_to_log(LogType::Info, "Copied diagnostics to clipboard.".to_owned());
+            ui.close_menu();
+        }
+        if ui.button("About").clicked() {
+            WINDOW.lock().unwrap().about_open = true;
+            ui.close_menu();
+        }
+    }
+
+    fn stop_game(&mut self)
+    {
+        let system = System::new_all();
+        if system.processes_by_exact_name(GAME_PROCESS_NAME).peekable().peek().is_some()
+        {
+            match Command::new("taskkill").args(["/f", "/im", GAME_PROCESS_NAME]).spawn()
+            {
+                Ok(_) => self.log.add_to_log(LogType::Info, "Stopping existing Guilty Gear Xrd process if it exists!".to_owned()),
+                Err(e) => self.log.add_to_log(LogType::Info, format!("Could not stop Guilty Gear Xrd process! {}", e)),
+            }    
+        }
+    }
+
+    fn setup_mods_and_play(&mut self)
+    {
+        self.apply_mod_set(self.mod_datas.clone(), ApplyPurpose::Play);
+    }
+
+    fn sandbox_path(&self) -> PathBuf
+    {
+        match &CONFIG.lock().unwrap().config.general.sandbox_path {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let mut exe_path = std::env::current_exe().unwrap();
+                exe_path.pop();
+                exe_path.join("sandbox")
             }
         }
-        else {
-            let mut mod_data: ModData = ModData::new();
-            mod_data.name = name.clone();
-            mod_data.path = Path::join(&self.mods_path, &name);
-            mod_data.write_data().unwrap_or_default();
-            init_mod_config(name, &mut mod_data, config);
-            self.write_config(config);
-            self.mod_datas.push(mod_data);
-            self.log.add_to_log(LogType::Warn, format!("No mod ini at path {}! Created one automatically.", &path.display()));
+    }
+
+    /// Applies the enabled mods to a throwaway copy of the game layout instead of the real game, so
+    /// authors can inspect the copied files and DefaultEngine.ini. The game is not launched.
+    fn sandbox_apply(&mut self)
+    {
+        let sandbox_path = self.sandbox_path();
+        let config_path = Path::join(&sandbox_path, "REDGame").join("Config");
+        let prepared = fs::create_dir_all(Path::join(&sandbox_path, "REDGame").join("CookedPCConsole"))
+            .and_then(|_| fs::create_dir_all(&config_path))
+            .and_then(|_| fs::copy(Path::join(&self.game_path, "REDGame").join("Config").join("DefaultEngine.ini"), config_path.join("DefaultEngine.ini")));
+        if let Err(e) = prepared {
+            self.log.add_to_log(LogType::Error, format!("Could not prepare sandbox folder {}! {}", sandbox_path.display(), e));
+            return
+        }
+        self.log.add_to_log(LogType::Info, format!("Test applying mods in sandbox folder {}.", sandbox_path.display()));
+        let game_path = std::mem::replace(&mut self.game_path, sandbox_path);
+        self.apply_mod_set(self.mod_datas.clone(), ApplyPurpose::Sandbox);
+        self.game_path = game_path;
+    }
+
+    fn safe_launch(&mut self)
+    {
+        let mut mod_datas = self.mod_datas.clone();
+        for mod_data in &mut mod_datas {
+            mod_data.enabled = mod_data.known_good;
         }
+        let included: Vec<&str> = mod_datas.iter().filter(|mod_data| mod_data.enabled).map(|mod_data| mod_data.name.as_str()).collect();
+        match included.is_empty() {
+            true => self.log.add_to_log(LogType::Info, "Safe launch: no mods are marked as known good, launching without mods.".to_owned()),
+            false => self.log.add_to_log(LogType::Info, format!("Safe launch with {} known good mod(s): {}", included.len(), included.join(", "))),
+        }
+        self.apply_mod_set(mod_datas, ApplyPurpose::SafeLaunch);
     }
 
-    fn install_mod(&mut self, path: PathBuf, config: &mut ConfigState)
+    fn launch_game(&mut self, mod_datas: &[ModData])
     {
-        let file_type: i32 = match path.extension().and_then(OsStr::to_str)
+        let launched = match self.launch_method {
+            LaunchMethod::Direct => match self.launch_direct(mod_datas) {
+                Ok(_) => Ok(()),
+                Err(e) => {
+                    self.log.add_to_log(LogType::Warn, format!("{} Falling back to launching through Steam.", e));
+                    self.launch_steam()
+                }
+            },
+            LaunchMethod::Steam => self.launch_steam(),
+        };
+        match launched
         {
-            Some("zip") => 0,
-            Some("7z") => 1,
-            Some("rar") => 2,
-            _ => 3,
+            Ok(_) => {
+                self.log.add_to_log(LogType::Info, "Launching Guilty Gear Xrd Rev 2...".to_string());
+                if self.interactive {
+                    self.launch_watch = Some(LaunchWatch {
+                        launched: Instant::now(),
+                        started: None,
+                        last_check: Instant::now(),
+                    });
+                }
+            }
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not launch Guilty Gear Xrd Rev 2! {}", e)),
+        }
+    }
+
+    fn launch_steam(&self) -> std::io::Result<()>
+    {
+        open::that(format!("steam://run/{}", self.app_id))
+    }
+
+    fn launch_direct(&mut self, mod_datas: &[ModData]) -> Result<(), String>
+    {
+        let exe_path = Path::join(&self.game_path, "Binaries").join("Win32").join(GAME_PROCESS_NAME);
+        if !exe_path.exists() {
+            return Err(format!("Could not find {}!", exe_path.display()))
+        }
+        let (args, conflicts) = launch_args(&self.launch_options, mod_datas);
+        for conflict in conflicts {
+            self.log.add_to_log(LogType::Warn, conflict);
+        }
+        if !args.is_empty() {
+            self.log.add_to_log(LogType::Info, format!("Launch arguments: {}", args.join(" ")));
+        }
+        Command::new(&exe_path)
+            .args(&args)
+            .current_dir(exe_path.parent().unwrap_or(&self.game_path))
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| format!("Could not start {}! {}", exe_path.display(), e))
+    }
+
+    fn poll_launch_watch(&mut self)
+    {
+        let watch = match &mut self.launch_watch {
+            Some(watch) if watch.last_check.elapsed() >= Duration::from_secs(1) => watch,
+            _ => return,
         };
-        let file_stem = match path.file_stem() {
-            Some(file_stem) => file_stem,
-            None => {
-                self.log.add_to_log(LogType::Error, "File has no name!".to_owned());
+        watch.last_check = Instant::now();
+        let mut system = System::new();
+        system.refresh_processes();
+        let running = system.processes_by_exact_name(GAME_PROCESS_NAME).next().is_some();
+        match (watch.started, running) {
+            (None, true) => watch.started = Some(Instant::now()),
+            (None, false) if watch.launched.elapsed() >= Duration::from_secs(LAUNCH_TIMEOUT_SECONDS) => self.launch_watch = None,
+            (Some(started), false) => {
+                if started.elapsed() < Duration::from_secs(QUICK_EXIT_SECONDS) {
+                    self.log.add_to_log(LogType::Warn, format!("Guilty Gear Xrd Rev 2 closed {:.1}s after starting! A mod may have crashed the game.", started.elapsed().as_secs_f32()));
+                    self.crash_prompt_open = true;
+                }
+                self.launch_watch = None;
+            }
+            (Some(started), true) if started.elapsed() >= Duration::from_secs(QUICK_EXIT_SECONDS) => self.launch_watch = None,
+            _ => (),
+        }
+    }
+
+    fn open_game_logs(&mut self)
+    {
+        let logs_path = Path::join(&self.game_path, "REDGame").join("Logs");
+        match open::that(&logs_path) {
+            Ok(_) => self.log.add_to_log(LogType::Info, format!("Opened game logs at {}.", logs_path.display())),
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not open game logs at {}! {}", logs_path.display(), e)),
+        }
+    }
+
+    /// Counts how often each enabled mod was applied, so mods that are never used stand out.
+    fn record_apply_stats(&mut self, mod_datas: &[ModData])
+    {
+        let now = chrono::Utc::now().timestamp();
+        let mut config = CONFIG.lock().unwrap();
+        for mod_data in mod_datas.iter().filter(|mod_data| mod_data.enabled) {
+            config.config.record_apply(&mod_folder_name(mod_data), now);
+        }
+        self.write_config(&mut config);
+        self.sync_mod_settings(&config);
+    }
+
+    /// Checks and prepares the game folder, then copies the enabled mods on a separate thread so the
+    /// window can show progress. `purpose` decides what happens once the copy finishes.
+    fn apply_mod_set(&mut self, mod_datas: Vec<ModData>, purpose: ApplyPurpose)
+    {
+        if self.running_apply.is_some() {
+            self.log.add_to_log(LogType::Warn, "Mods are already being applied! Wait for it to finish first.".to_owned());
+            return
+        }
+        let enabled_count = mod_datas.iter().filter(|mod_data| mod_data.enabled).count();
+        if self.max_enabled_mods > 0 && enabled_count > self.max_enabled_mods {
+            self.log.add_to_log(LogType::Error, format!("Refusing to apply mods! {} mods are enabled, but at most {} are allowed by MaxEnabledMods.", enabled_count, self.max_enabled_mods));
+            return
+        }
+        let pairs = incompatible_pairs(&mod_datas);
+        if !pairs.is_empty() {
+            for (first, second) in &pairs {
+                self.log.add_to_log(LogType::Warn, format!("Mods {} and {} are incompatible!", first, second));
+            }
+            if self.block_incompatible_mods {
+                self.log.add_to_log(LogType::Error, "Refusing to apply mods because incompatible mods are enabled!".to_owned());
+                self.incompatibility_report = Some(incompatibility_report(&pairs));
+                return
+            }
+        }
+        let game_mods_path = match self.checked_game_mods_path() {
+            Ok(path) => path,
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Refusing to apply mods! {} Check the game path in your settings.", e));
                 return
             }
         };
-        match file_type {
-            0 => {
-                match std::fs::read(&path) {
-                    Ok(bytes) => {
-                        match zip_extract::extract(Cursor::new(bytes), 
-                            &Path::join(&self.mods_path, file_stem), true)
-                        {
-                            Ok(_) => self.init_mod(file_stem.to_str().unwrap().to_owned(), config),
-                            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not extract archive! {}", e))
-                        }
-                    }
-                    Err(e) => {
-                        self.log.add_to_log(LogType::Error, format!("Could not read archive! {}", e))
+        self.log.add_to_log(LogType::Info, format!("Applying mods to {}.", game_mods_path.display()));
+        match self.preserve_unmanaged_mods {
+            true => self.remove_managed_mods(&game_mods_path),
+            false => fs::remove_dir_all(helpers::long_path(&game_mods_path)).unwrap_or_default(),
+        }
+        let mut folder_index: usize = 0;
+        let folder_count = mod_datas.iter().filter(|mod_data| mod_data.enabled).count();
+        self.check_folder_template(&mod_datas, folder_count);
+        // Mods stored compressed are extracted to temporary folders first and copied from there.
+        let mut unpacked: Vec<TempDir> = Vec::new();
+        // Folders are picked before copying starts, so also remember the ones taken by earlier mods.
+        let mut taken: HashSet<PathBuf> = HashSet::new();
+        let mut jobs: Vec<apply::ApplyJob> = Vec::new();
+        for (index, mod_data) in mod_datas.iter().enumerate().rev() {
+            if !mod_data.enabled {
+                continue
+            }
+            let source = match &mod_data.packed {
+                Some(packed) => match self.unpack_mod(mod_data, packed) {
+                    Some(staging) => {
+                        let path = staging.path().to_path_buf();
+                        unpacked.push(staging);
+                        path
                     }
+                    None => continue,
+                },
+                None => mod_data.path.clone(),
+            };
+            let in_use = |folder: &Path| taken.contains(folder) || Path::join(&game_mods_path, folder).exists();
+            let mut folder = self.mod_folder(folder_index, folder_count, mod_data);
+            while self.folder_template.contains("{order}") && in_use(&folder) {
+                folder_index += 1;
+                folder = self.mod_folder(folder_index, folder_count, mod_data);
+            }
+            // Without {order}, two mods can render to the same folder and would be merged into it.
+            if !self.folder_template.contains("{order}") && in_use(&folder) {
+                let name = folder.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                let mut number = 2;
+                let mut renamed = folder.with_file_name(format!("{} ({})", name, number));
+                while in_use(&renamed) {
+                    number += 1;
+                    renamed = folder.with_file_name(format!("{} ({})", name, number));
                 }
+                self.log.add_to_log(LogType::Warn, format!("FolderTemplate {} puts mod {} in folder {}, which is already used! Using {} instead.",
+                    self.folder_template, mod_data.name, folder.display(), renamed.display()));
+                folder = renamed;
             }
-            1 => {
-                match sevenz_rust::decompress_file(&path, Path::join(&self.mods_path, file_stem))
-                {
-                    Ok(_) => self.init_mod(file_stem.to_str().unwrap().to_owned(), config),
-                    Err(e) => self.log.add_to_log(LogType::Error, format!("Could not extract archive! {}", e))
-                }        
+            folder_index += 1;
+            taken.insert(folder.clone());
+            jobs.push(apply::ApplyJob {
+                index,
+                name: mod_data.name.clone(),
+                scripts: mod_data.scripts.clone(),
+                source,
+                folder: folder.display().to_string(),
+                destination: Path::join(&game_mods_path, &folder),
+                exclude: self.exclude_set(mod_data),
+            });
+        }
+        let running = RunningApply {
+            task: ApplyTask::start(jobs, self.hardlink_duplicates, self.verify_apply),
+            mod_datas,
+            game_path: self.game_path.clone(),
+            game_mods_path,
+            purpose,
+            start: Instant::now(),
+            _unpacked: unpacked,
+        };
+        // Without a window to show progress in, wait for the copy right away.
+        match self.interactive {
+            true => self.running_apply = Some(running),
+            false => self.finish_apply(running, false),
+        }
+    }
+
+    fn poll_apply(&mut self)
+    {
+        match &self.running_apply {
+            Some(running) if running.task.is_finished() => (),
+            _ => return,
+        }
+        let running = self.running_apply.take().unwrap();
+        self.finish_apply(running, false);
+    }
+
+    /// Reports the copied mods, writes the script packages and applied_mods.txt, and then continues
+    /// with whatever the apply was started for. When `closing`, the game is not launched and the
+    /// sandbox is not opened, since the mod manager is about to exit.
+    fn finish_apply(&mut self, running: RunningApply, closing: bool)
+    {
+        let results = match running.task.join() {
+            Ok(results) => results,
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Could not apply mods! {}", e));
+                return
             }
-            2 => {
-                match unrar::Archive::new(&path) {
-                    Ok(archive) => 
-                    {
-                        match archive.extract_to(Path::join(&self.mods_path, file_stem))
-                        {
-                            Ok(mut archive) => {
-                                match archive.process() {
-                                    Ok(_) => self.init_mod(file_stem.to_str().unwrap().to_owned(), config),
-                                    Err(e) => self.log.add_to_log(LogType::Error, format!("Could not extract archive! {}", e))
-                                }
-                            },
-                            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not extract archive! {}", e))
-                        }        
+        };
+        let mod_datas = &running.mod_datas;
+        let mut applied_mods: Vec<(String, &ModData)> = Vec::new();
+        let mut copy_stats = helpers::CopyStats::default();
+        let mut verify_failures: Vec<(String, Vec<PathBuf>)> = Vec::new();
+        let mut scripts: Vec<String> = Vec::new();
+        for result in results {
+            let mod_data = &mod_datas[result.index];
+            match result.copy {
+                Ok(stats) => {
+                    copy_stats.files += stats.files;
+                    copy_stats.bytes += stats.bytes;
+                    copy_stats.linked += stats.linked;
+                    copy_stats.linked_bytes += stats.linked_bytes;
+                    if stats.skipped_links > 0 {
+                        self.log.add_to_log(LogType::Warn, format!("Skipped {} broken or looping links while copying mod {}.", stats.skipped_links, &mod_data.name));
                     }
-                    Err(e) => {
-                        self.log.add_to_log(LogType::Error, format!("Could not read archive! {}", e))
+                    match result.verify {
+                        Some(Ok(failed)) if !failed.is_empty() => verify_failures.push((mod_data.name.clone(), failed)),
+                        Some(Err(e)) => self.log.add_to_log(LogType::Error, format!("Could not verify mod {}! {}", &mod_data.name, e)),
+                        _ => (),
                     }
+                    applied_mods.push((result.folder, mod_data));
+                }
+                Err(e) => {
+                    self.log.add_to_log(LogType::Error, format!("Could not copy mod {}! {}", &mod_data.name, e));
+                    continue;
                 }
             }
-            _ => {
-                self.log.add_to_log(LogType::Error, "Invalid file extension!".to_string())
+            for script in &result.missing_scripts {
+                self.log.add_to_log(LogType::Warn, format!("Mod {} declares script package {}, but no matching .u or .upk file was found! Skipping it.", &mod_data.name, script));
+            }
+            scripts.extend(result.scripts);
+        }
+        self.write_script_packages(&running.game_path, &scripts);
+        self.log.add_to_log(LogType::Info, "Mods copied to game directory!".to_string());
+        self.log.add_to_log(LogType::Info, format!("Applied {} mods ({} files, {}) in {:.1}s.",
+            applied_mods.len(),
+            helpers::format_count(copy_stats.files),
+            helpers::format_size(copy_stats.bytes),
+            running.start.elapsed().as_secs_f32()));
+        if copy_stats.linked > 0 {
+            self.log.add_to_log(LogType::Info, format!("Hardlinked {} duplicate files instead of copying them, saving {}.",
+                helpers::format_count(copy_stats.linked),
+                helpers::format_size(copy_stats.linked_bytes)));
+        }
+        if self.verify_apply {
+            self.report_verification(applied_mods.len(), &verify_failures);
+        }
+        self.write_applied_log(&running.game_mods_path, &applied_mods);
+        match running.purpose {
+            ApplyPurpose::Play => {
+                self.record_apply_stats(mod_datas);
+                self.record_history();
+                self.last_manifest.clear();
+                self.write_manifest();
+                if !closing {
+                    self.launch_game(mod_datas);
+                }
+            }
+            ApplyPurpose::SafeLaunch => {
+                if !closing {
+                    self.launch_game(mod_datas);
+                }
+            }
+            ApplyPurpose::Sandbox => {
+                self.log.add_to_log(LogType::Info, "Sandbox apply finished. Your game installation was not changed.".to_owned());
+                if !closing {
+                    open::that(&running.game_path).unwrap_or_default();
+                }
             }
         }
     }
 
-    fn file_menu(&mut self, ui: &mut Ui, config: &mut ConfigState)
+    fn unpack_mod(&mut self, mod_data: &ModData, packed: &Path) -> Option<TempDir>
     {
-        if ui.button("Install Mod").clicked() {
-            if let Some(path) = rfd::FileDialog::new()
-            .add_filter("All supported archives", &["zip", "rar", "7z"])
+        let staging = match self.download_settings.create_temp_dir() {
+            Ok(staging) => staging,
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Could not create temporary directory! Skipping compressed mod {}. {}", mod_data.name, e));
+                return None
+            }
+        };
+        match archive::extract(packed, staging.path(), self.download_settings.temp_dir.as_deref()) {
+            Ok(()) => Some(staging),
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Could not extract compressed mod {}! Skipping it. {}", mod_data.name, e));
+                None
+            }
+        }
+    }
+
+    fn write_script_packages(&mut self, game_path: &Path, scripts: &[String])
+    {
+        let ini_path = Path::join(game_path, "REDGame").join("Config").join("DefaultEngine.ini");
+        let mut ini = match Ini::load_from_file_noescape(&ini_path) {
+            Ok(ini) => ini,
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Could not read DefaultEngine.ini! {}", e));
+                return
+            }
+        };
+        let (removed, added) = match ini.section_mut(Some("Engine.ScriptPackages")) {
+            Some(section) => helpers::sync_script_packages(section, scripts),
+            None => {
+                self.log.add_to_log(LogType::Error, "Could not find Engine.ScriptPackages in DefaultEngine.ini! Your game installation may be broken.".to_owned());
+                return
+            }
+        };
+        for package in &removed {
+            self.log.add_to_log(LogType::Info, format!("Removed script package {}.", package));
+        }
+        for package in &added {
+            self.log.add_to_log(LogType::Info, format!("Added script package {}!", package));
+        }
+        match ini.write_to_file_policy(&ini_path, EscapePolicy::Nothing) {
+            Ok(_) => (),
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not write to DefaultEngine.ini! {}", e)),
+        }
+    }
+
+    fn exclude_set(&mut self, mod_data: &ModData) -> GlobSet
+    {
+        let mut exclude = GlobSetBuilder::new();
+        for pattern in &mod_data.exclude {
+            match Glob::new(pattern) {
+                Ok(glob) => { exclude.add(glob); }
+                Err(e) => self.log.add_to_log(LogType::Warn, format!("Mod {} has an invalid exclude pattern {}! Ignoring it. {}", &mod_data.name, pattern, e)),
+            }
+        }
+        exclude.build().unwrap_or_else(|_| GlobSet::empty())
+    }
+
+    fn export_mod(&mut self, mod_data: &ModData)
+    {
+        let file_name = match mod_data.version.trim() {
+            "" => format!("{}.zip", mod_data.name),
+            version => format!("{}-{}.zip", mod_data.name, version),
+        };
+        let path = match rfd::FileDialog::new()
             .add_filter("ZIP archive", &["zip"])
-            .add_filter("7Z archive", &["7z"])
-            .add_filter("RAR archive", &["rar"])
-            .pick_file() {
-                self.install_mod(path, config)
+            .set_file_name(&helpers::sanitize_folder_name(&file_name))
+            .save_file() {
+                Some(path) => path,
+                None => return,
             };
-            ui.close_menu();
+        let exclude = self.exclude_set(mod_data);
+        let root = mod_data.path.file_name().map(|folder| folder.to_string_lossy().into_owned());
+        match backup::zip_folder(&mod_data.path, &path, root.as_deref(), &exclude) {
+            Ok(files) => self.log.add_to_log(LogType::Info, format!("Exported mod {} ({} files) to {}.", mod_data.name, helpers::format_count(files), path.display())),
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not export mod {}! {}", mod_data.name, e)),
         }
-        let mut window = WINDOW.lock().unwrap();
-        if ui.button("Create Mod").clicked() {
-            window.create_open = true;
-            ui.close_menu();
+    }
+
+    fn mod_folder(&self, index: usize, count: usize, mod_data: &ModData) -> PathBuf
+    {
+        let folder = helpers::folder_from_template(&self.folder_template, &helpers::mod_folder_name(index, count), &mod_data.name, &mod_data.author);
+        match folder.as_os_str().is_empty() {
+            true => helpers::folder_from_template(DEFAULT_FOLDER_TEMPLATE, &helpers::mod_folder_name(index, count), &mod_data.name, &mod_data.author),
+            false => folder,
         }
-        if ui.button("Locate Mod").clicked() {
-            if let Some(path) = rfd::FileDialog::new()
-            .add_filter("INI file", &["ini"])
-            .pick_file() {
-                let mut name = path.clone();
-                name.pop();
-                self.init_mod(name.display().to_string(), config)
-            }
-            ui.close_menu()
+    }
+
+    fn check_folder_template(&mut self, mod_datas: &[ModData], count: usize)
+    {
+        let folders: Vec<String> = mod_datas.iter().rev()
+            .filter(|mod_data| mod_data.enabled)
+            .enumerate()
+            .map(|(index, mod_data)| self.mod_folder(index, count, mod_data).display().to_string().to_lowercase())
+            .collect();
+        if folders.windows(2).any(|pair| pair[0] >= pair[1]) {
+            self.log.add_to_log(LogType::Warn, format!("FolderTemplate {} does not sort mod folders in load order! The game may load mods in a different order than shown. Start the template with {{order}} to fix this.", self.folder_template));
         }
     }
 
-    fn settings_menu(&mut self, ui: &mut Ui)
+    fn report_verification(&mut self, applied: usize, failures: &[(String, Vec<PathBuf>)])
     {
-        if ui.checkbox(&mut self.console_visible, "Show Console").changed() {
-            ui.close_menu();
+        if failures.is_empty() {
+            self.log.add_to_log(LogType::Info, format!("Verified {} mods. All files are in place.", applied));
+            return
         }
+        for (name, files) in failures {
+            let listed: Vec<String> = files.iter().take(5).map(|file| file.display().to_string()).collect();
+            let more = match files.len() > listed.len() {
+                true => format!(" and {} more", files.len() - listed.len()),
+                false => "".to_owned(),
+            };
+            self.log.add_to_log(LogType::Error, format!("{} file(s) of mod {} are missing or incomplete in the game folder: {}{}", files.len(), name, listed.join(", "), more));
+        }
+        self.log.add_to_log(LogType::Error, format!("Verification failed for {} of {} mods! If your antivirus quarantined the files, restore them and add an exception for the game folder.", failures.len(), applied));
     }
 
-    fn setup_mods_and_play(&mut self)
+    /// Returns the resolved game Mods folder, or why it is not safe to write to. Callers log the
+    /// returned path themselves, since this also runs for checks that should not add to the log.
+    fn checked_game_mods_path(&mut self) -> Result<PathBuf, String>
+    {
+        if self.game_path.as_os_str().is_empty() {
+            return Err("The game path is not set!".to_owned())
+        }
+        let cooked_path = Path::join(&self.game_path, "REDGame").join("CookedPCConsole");
+        if !cooked_path.is_dir() {
+            return Err(format!("{} does not exist, so {} is not a Guilty Gear Xrd installation!", cooked_path.display(), self.game_path.display()))
+        }
+        // Resolve the folder that is known to exist, so the result is comparable with the canonical
+        // game path (which starts with \\?\ on Windows) even before Mods is created.
+        let cooked_path = fs::canonicalize(&cooked_path).map_err(|e| format!("Could not resolve {}! {}", cooked_path.display(), e))?;
+        let game_mods_path = cooked_path.join("Mods");
+        let resolved = match game_mods_path.exists() {
+            true => fs::canonicalize(&game_mods_path).map_err(|e| format!("Could not resolve {}! {}", game_mods_path.display(), e))?,
+            false => game_mods_path,
+        };
+        let game_path = fs::canonicalize(&self.game_path).map_err(|e| format!("Could not resolve {}! {}", self.game_path.display(), e))?;
+        if !resolved.starts_with(&game_path) || resolved == game_path {
+            return Err(format!("The game Mods folder resolved to {}, which is not inside {}!", resolved.display(), game_path.display()))
+        }
+        Ok(resolved)
+    }
+
+    /// Lists what the mod manager left behind in the game: files in the Mods folder and
+    /// script packages besides REDGame in DefaultEngine.ini.
+    fn clean_install_leftovers(&mut self) -> Result<Vec<String>, String>
     {
+        let game_mods_path = self.checked_game_mods_path()?;
+        let mut leftovers = Vec::new();
+        if let Ok(entries) = fs::read_dir(&game_mods_path) {
+            let names: Vec<String> = entries.flatten().map(|entry| entry.file_name().to_string_lossy().into_owned()).collect();
+            if !names.is_empty() {
+                leftovers.push(format!("{} contains {}.", game_mods_path.display(), names.join(", ")));
+            }
+        }
         let ini_path = Path::join(&self.game_path, "REDGame").join("Config").join("DefaultEngine.ini");
-        let ini: Result<Ini, ini::Error> = Ini::load_from_file_noescape(&ini_path);
-        match ini {
-            Ok(mut ini) => 
-            {
-                match ini.section_mut(Some("Engine.ScriptPackages"))
-                {
-                    Some(section) => {
-                        for _ in section.remove_all("+NativePackages") {}
-                        section.append("+NativePackages", "REDGame");
-                        match ini.write_to_file_policy(&ini_path, EscapePolicy::Nothing) {
-                            Ok(_) => (),
-                            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not write to DefaultEngine.ini! {}", e)),
-                        }    
-                    }
-                    None => self.log.add_to_log(LogType::Error, "Could not find Engine.ScriptPackages in DefaultEngine.ini! Your game installation may be broken.".to_owned()),
+        let ini = Ini::load_from_file_noescape(&ini_path).map_err(|e| format!("Could not read DefaultEngine.ini! {}", e))?;
+        let packages: Vec<&str> = match ini.section(Some("Engine.ScriptPackages")) {
+            Some(section) => section.get_all("+NativePackages").collect(),
+            None => return Err("Could not find Engine.ScriptPackages in DefaultEngine.ini! Your game installation may be broken.".to_owned()),
+        };
+        let extra: Vec<&str> = packages.iter().copied().filter(|package| *package != "REDGame").collect();
+        if !extra.is_empty() {
+            leftovers.push(format!("DefaultEngine.ini still loads script packages {}.", extra.join(", ")));
+        }
+        if !packages.contains(&"REDGame") {
+            leftovers.push("DefaultEngine.ini does not load the base REDGame script package.".to_owned());
+        }
+        Ok(leftovers)
+    }
+
+    fn check_clean_install(&mut self)
+    {
+        if self.running_apply.is_some() {
+            self.log.add_to_log(LogType::Warn, "Mods are being applied! Wait for it to finish before checking the game.".to_owned());
+            return
+        }
+        match self.clean_install_leftovers() {
+            Ok(leftovers) if leftovers.is_empty() => {
+                self.log.add_to_log(LogType::Info, "Clean uninstall check passed. No mods are applied and only REDGame is loaded.".to_owned());
+                self.clean_check = Some(leftovers);
+            }
+            Ok(leftovers) => {
+                for leftover in &leftovers {
+                    self.log.add_to_log(LogType::Warn, format!("Clean uninstall check: {}", leftover));
                 }
+                self.clean_check = Some(leftovers);
+            }
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not run the clean uninstall check! {}", e)),
         }
-            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not read DefaultEngine.ini! {}", e)),
+    }
+
+    /// Removes the game's Mods folder and resets the script packages, like applying with no mods enabled.
+    fn clean_install(&mut self)
+    {
+        // The apply thread is still copying into the game's Mods folder.
+        if self.running_apply.is_some() {
+            self.log.add_to_log(LogType::Warn, "Mods are being applied! Wait for it to finish before cleaning up the game.".to_owned());
+            return
         }
-        fs::remove_dir_all(Path::join(&self.game_path, "REDGame").join("CookedPCConsole").join("Mods")).unwrap_or_default();
-        for mod_data in self.mod_datas.iter().rev() {
-            if mod_data.enabled {
-                let mut folder_string = "a".to_owned();
-                let game_mods_path = Path::join(&self.game_path, "REDGame").join("CookedPCConsole").join("Mods");
-                while Path::join(&game_mods_path, &folder_string).exists() {
-                    let tmp_string = helpers::add1_str(&folder_string);
-                    if folder_string != tmp_string {
-                        folder_string = tmp_string;
-                    }
-                    else {
-                        self.log.add_to_log(LogType::Error, format!("Could not copy mod {}! Too many mods installed.", &mod_data.name));
-                        break;
+        match self.checked_game_mods_path() {
+            Ok(game_mods_path) => {
+                self.log.add_to_log(LogType::Info, format!("Removing {}.", game_mods_path.display()));
+                if let Err(e) = fs::remove_dir_all(helpers::long_path(&game_mods_path)) {
+                    if e.kind() != std::io::ErrorKind::NotFound {
+                        self.log.add_to_log(LogType::Error, format!("Could not remove {}! {}", game_mods_path.display(), e));
                     }
                 }
-                match helpers::copy_recursively(&mod_data.path, Path::join(&game_mods_path, &folder_string).join(&mod_data.name))
-                {
-                    Ok(_) => (),
-                    Err(e) => {
-                        self.log.add_to_log(LogType::Error, format!("Could not copy mod {}! {}", &mod_data.name, e));
-                        continue;
-                    }
+            }
+            Err(e) => {
+                self.log.add_to_log(LogType::Error, format!("Refusing to clean up the game! {}", e));
+                return
+            }
+        }
+        self.write_script_packages(&self.game_path.clone(), &[]);
+        self.check_clean_install();
+    }
+
+    fn remove_managed_mods(&mut self, game_mods_path: &Path)
+    {
+        let applied_log = fs::read_to_string(game_mods_path.join("applied_mods.txt")).unwrap_or_default();
+        let managed: HashSet<String> = applied_log.lines()
+            .skip(1)
+            .filter_map(|line| line.split('\t').next())
+            .filter_map(|folder| folder.split(|c| c == '/' || c == '\\').next())
+            .filter(|folder| !folder.is_empty())
+            .map(|folder| folder.to_owned())
+            .collect();
+        for folder in &managed {
+            if let Err(e) = fs::remove_dir_all(helpers::long_path(game_mods_path.join(folder))) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    self.log.add_to_log(LogType::Error, format!("Could not remove previously applied mod folder {}! {}", folder, e));
                 }
-                let ini_path: PathBuf = Path::join(&self.game_path, "REDGame").join("Config").join("DefaultEngine.ini");
-                let ini: Result<Ini, ini::Error> = Ini::load_from_file_noescape(&ini_path);
-                match ini {
-                    Ok(mut ini) => {
-                        for script in &mod_data.scripts {
-                            match ini.section_mut(Some("Engine.ScriptPackages"))
-                            {
-                                Some(section) => {
-                                    if section.get_all("+NativePackages").find(|x| x == script).is_none() {
-                                        section.append("+NativePackages", script);
-                                        self.log.add_to_log(LogType::Info, format!("Added script package {}!", script))
-                                    }
-                                }
-                                None => self.log.add_to_log(LogType::Error, "Could not read find Engine.ScriptPackages in DefaultEngine.ini! Your game installation may be broken.".to_owned()),
-                            }
-                        }
-                        match ini.write_to_file_policy(&ini_path, EscapePolicy::Nothing) {
-                            Ok(_) => (),
-                            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not write to DefaultEngine.ini! {}", e)),
-                        }
-                    }
-                    Err(e) => self.log.add_to_log(LogType::Error, format!("Could not read DefaultEngine.ini! {}", e)),
-                }    
             }
         }
-        self.log.add_to_log(LogType::Info, "Mods copied to game directory!".to_string());
-        match open::that("steam://run/520440")
-        {
-            Ok(_) => self.log.add_to_log(LogType::Info, "Launching Guilty Gear Xrd Rev 2...".to_string()),
-            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not launch Guilty Gear Xrd Rev 2! {}", e)),
+        fs::remove_file(game_mods_path.join("applied_mods.txt")).unwrap_or_default();
+        let unmanaged: Vec<String> = match fs::read_dir(game_mods_path) {
+            Ok(entries) => entries.flatten()
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        if !unmanaged.is_empty() {
+            self.log.add_to_log(LogType::Warn, format!("Keeping {} folder(s) in the game's Mods folder that weren't applied by the mod manager: {}. Their load order relative to managed mods depends on folder names.", unmanaged.len(), unmanaged.join(", ")));
+        }
+    }
+
+    fn write_applied_log(&mut self, game_mods_path: &Path, applied_mods: &[(String, &ModData)])
+    {
+        let mut applied_log = format!("Applied by GUILTY GEAR Xrd Mod Manager {} on {}\n",
+            cargo_crate_version!(), Local::now().format("%Y-%m-%d %H:%M:%S"));
+        for (folder, mod_data) in applied_mods {
+            let scripts = match mod_data.scripts.is_empty() {
+                true => "none".to_owned(),
+                false => mod_data.scripts.join(", "),
+            };
+            applied_log += &format!("{}\t{}\tScripts: {}\n", folder, mod_data.name, scripts);
+        }
+        match fs::create_dir_all(game_mods_path).and_then(|_| fs::write(game_mods_path.join("applied_mods.txt"), applied_log)) {
+            Ok(_) => (),
+            Err(e) => self.log.add_to_log(LogType::Error, format!("Could not write applied_mods.txt! {}", e)),
         }
     }
 }
 
 impl eframe::App for ManagerState {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame)
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame)
     {
+        self.update_tray(ctx, frame);
+        self.check_clipboard(ctx);
+        self.install_dropped_files(ctx);
+        self.apply_theme(ctx);
+        if !self.ui_scale_dragging {
+            self.apply_ui_scale(ctx, frame);
+        }
+
         egui::TopBottomPanel::top("header_panel").show(ctx, |ui: &mut Ui| {
             ui.horizontal(|ui| {
                 ui.menu_button("File", |ui| {
@@ -763,77 +3702,83 @@ impl eframe::App for ManagerState {
                 ui.menu_button("Settings", |ui| {
                     self.settings_menu(ui)
                 });
+                ui.menu_button("Tools", |ui| {
+                    self.tools_menu(ui)
+                });
                 ui.menu_button("Help", |ui| {
-                    help_menu(ui)
+                    self.help_menu(ui)
                 });
-                let mut visuals = ui.ctx().style().visuals.clone();
-                visuals.light_dark_radio_buttons(ui);
-                ui.ctx().set_visuals(visuals);
+                let mut theme = self.theme;
+                ui.selectable_value(&mut theme, Theme::Light, "☀ Light");
+                ui.selectable_value(&mut theme, Theme::Dark, "🌙 Dark");
+                ui.selectable_value(&mut theme, Theme::System, "💻 System");
+                if theme != self.theme {
+                    self.theme = theme;
+                    self.last_theme_check = None;
+                    let mut config = CONFIG.lock().unwrap();
+                    config.config.general.theme = theme;
+                    self.write_config(&mut config);
+                }
             });
         });
         
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui: &mut Ui| {
+            ui.horizontal(|ui| {
+                let enabled_count = self.mod_datas.iter().filter(|mod_data| mod_data.enabled).count();
+                match self.max_enabled_mods {
+                    0 => ui.label(format!("{} of {} mods enabled", enabled_count, self.mod_datas.len())),
+                    limit if enabled_count >= limit => ui.label(RichText::new(format!("{} of {} mods enabled (limit {} reached)", enabled_count, self.mod_datas.len(), limit))
+                        .color(ui.visuals().warn_fg_color)),
+                    limit => ui.label(format!("{} of {} mods enabled (limit {})", enabled_count, self.mod_datas.len(), limit)),
+                };
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    match (self.config_dirty, self.config_save_failed) {
+                        (true, _) => ui.label("Saving…").on_hover_text("Changes will be written to config.ini shortly."),
+                        (false, true) => ui.label(RichText::new("Save failed ✖").color(ui.visuals().error_fg_color))
+                            .on_hover_text("config.ini could not be written. Check the log for details."),
+                        (false, false) => ui.label(RichText::new("Saved ✔").weak()).on_hover_text("All changes are saved to config.ini."),
+                    };
+                });
+            });
+        });
+
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F12)) {
+            self.console_visible = !self.console_visible;
+            self.save_console_layout();
+        }
+
         if self.console_visible
         {
+            let (info_color, warn_color, error_color, text_color) = console_colors(self.high_contrast, ctx.style().visuals.dark_mode);
+            let [info_override, warn_override, error_override] = self.console_color_overrides;
+            let info_color = info_override.unwrap_or(info_color);
+            let warn_color = warn_override.unwrap_or(warn_color);
+            let error_color = error_override.unwrap_or(error_color);
             let mut layouter = |ui: &Ui, string: &str, wrap_width: f32| {
                 let mut job = LayoutJob::default();
+                let font_id = FontId::new(14.0, FontFamily::Monospace);
                 for line in string.lines() {
-                    match line {
-                        s if s.starts_with("[INFO]") =>
-                        {
-                            job.append(
-                                line,
-                                0.0,
-                                TextFormat {
-                                    font_id: FontId::new(14.0, FontFamily::Monospace),
-                                    color: Color32::GREEN,
-                                    ..Default::default()
-                                },
-                            );            
-                        }
-                        s if s.starts_with("[WARN]") =>
-                        {
-                            job.append(
-                                line,
-                                0.0,
-                                TextFormat {
-                                    font_id: FontId::new(14.0, FontFamily::Monospace),
-                                    color: Color32::YELLOW,
-                                    ..Default::default()
-                                },
-                            );            
-                        }
-                        s if s.starts_with("[ERROR]") =>
-                        {
-                            job.append(
-                                line,
-                                0.0,
-                                TextFormat {
-                                    font_id: FontId::new(14.0, FontFamily::Monospace),
-                                    color: Color32::RED,
-                                    ..Default::default()
-                                },
-                            );            
-                        }
-                        _ => 
-                        {
-                            job.append(
-                                line,
-                                0.0,
-                                TextFormat {
-                                    font_id: FontId::new(14.0, FontFamily::Monospace),
-                                    color: Color32::WHITE,
-                                    ..Default::default()
-                                },
-                            );            
-    
-                        }
-                    }
+                    let color = match line {
+                        s if s.starts_with("[INFO]") => info_color,
+                        s if s.starts_with("[WARN]") => warn_color,
+                        s if s.starts_with("[ERROR]") => error_color,
+                        _ => text_color,
+                    };
+                    job.append(
+                        line,
+                        0.0,
+                        TextFormat {
+                            font_id: font_id.clone(),
+                            color,
+                            ..Default::default()
+                        },
+                    );
                     job.append(
                         "\n",
                         0.0,
                         TextFormat {
-                            font_id: FontId::new(14.0, FontFamily::Monospace),
-                            color: Color32::WHITE,
+                            font_id: font_id.clone(),
+                            color: text_color,
                             ..Default::default()
                         },
                     );
@@ -842,10 +3787,13 @@ impl eframe::App for ManagerState {
                 ui.fonts(|f| f.layout_job(job))
             };
     
-            egui::TopBottomPanel::bottom("console_panel")
+            let mut console_panel = egui::TopBottomPanel::bottom("console_panel")
             .max_height(300.)
-            .resizable(true)
-            .show(ctx, |ui: &mut Ui| {
+            .resizable(true);
+            if let Some(height) = self.console_height {
+                console_panel = console_panel.default_height(height);
+            }
+            let console_response = console_panel.show(ctx, |ui: &mut Ui| {
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     let mut log: &str = &self.log.log_text;
                     ui.add(
@@ -859,6 +3807,15 @@ impl eframe::App for ManagerState {
                         );
                 });
             });
+            let height = console_response.response.rect.height();
+            let resized = match self.console_height {
+                Some(saved) => (saved - height).abs() >= 1.,
+                None => true,
+            };
+            if resized {
+                self.console_height = Some(height);
+                self.save_console_layout();
+            }
         }
     
         egui::SidePanel::left("options_panel").show(ctx, |ui: &mut Ui| {
@@ -870,18 +3827,17 @@ impl eframe::App for ManagerState {
                 if ui.small_button("📁Manage Mods").clicked() {
     
                 }*/
+                if ui.small_button("🔄Refresh").on_hover_text("Pick up mods that were added, removed or renamed in the mods folder.").clicked() {
+                    self.reconcile_mods();
+                }
                 if ui.small_button("▶️Launch Game").clicked() {
-                    let system = System::new_all();
-                    if system.processes_by_exact_name("GuiltyGearXrd.exe").peekable().peek().is_some()
-                    {
-                        match Command::new("taskkill").args(["/f", "/im", "GuiltyGearXrd.exe"]).spawn()
-                        {
-                            Ok(_) => self.log.add_to_log(LogType::Info, "Stopping existing Guilty Gear Xrd process if it exists!".to_owned()),
-                            Err(e) => self.log.add_to_log(LogType::Info, format!("Could not stop Guilty Gear Xrd process! {}", e)),
-                        }    
-                    }
+                    self.stop_game();
                     self.setup_mods_and_play();
                 }
+                if ui.small_button("🛡Safe Launch").on_hover_text("Launch with only the mods marked as known good. Your enabled mods are not changed.").clicked() {
+                    self.stop_game();
+                    self.safe_launch();
+                }
             });
         });
     
@@ -890,25 +3846,177 @@ impl eframe::App for ManagerState {
             .min_width(280.)
             .show(ctx, |ui: &mut Ui| {
                 ui.vertical(|ui| {
+                    if !self.selected_mod.path.as_os_str().is_empty() {
+                        ui.label(RichText::new("Notes").strong());
+                        let notes_response = ui.add(egui::TextEdit::multiline(&mut self.selected_mod.notes)
+                            .hint_text("Private notes about this mod. These are only stored in your config.")
+                            .desired_rows(3)
+                            .desired_width(f32::INFINITY));
+                        if notes_response.changed() {
+                            let mut config = CONFIG.lock().unwrap();
+                            config.config.set_note(&mod_folder_name(&self.selected_mod), &self.selected_mod.notes);
+                            self.write_config(&mut config);
+                            self.sync_mod_settings(&config);
+                        }
+                        let mut scheduled = self.selected_mod.schedule.is_some();
+                        let mut schedule_changed = ui.checkbox(&mut scheduled, "Enable on a schedule")
+                            .on_hover_text("Enable this mod between two dates each year and disable it otherwise. Checked when the mod manager starts.")
+                            .changed();
+                        if scheduled {
+                            let today = Local::now().date_naive();
+                            let schedule = self.selected_mod.schedule.get_or_insert(Schedule { start: (today.month(), today.day()), end: (today.month(), today.day()) });
+                            ui.horizontal(|ui| {
+                                for (label, (month, day)) in [("From", &mut schedule.start), ("to", &mut schedule.end)] {
+                                    ui.label(label);
+                                    schedule_changed |= ui.add(egui::DragValue::new(month).clamp_range(1..=12).prefix("Month ")).changed();
+                                    schedule_changed |= ui.add(egui::DragValue::new(day).clamp_range(1..=31).prefix("Day ")).changed();
+                                }
+                            });
+                        }
+                        else {
+                            self.selected_mod.schedule = None;
+                        }
+                        if schedule_changed {
+                            let mut config = CONFIG.lock().unwrap();
+                            config.config.set_schedule(&mod_folder_name(&self.selected_mod), self.selected_mod.schedule);
+                            self.write_config(&mut config);
+                            self.sync_mod_settings(&config);
+                        }
+                        ui.separator();
+                    }
                     ui.label(format!("Author: {}", self.selected_mod.author));
                     ui.label(format!("Category: {}", self.selected_mod.category));
                     ui.label(format!("Description: {}", &self.selected_mod.description));
                     ui.label(format!("Version: {}", self.selected_mod.version));
+                    let last_applied = self.selected_mod.apply_stats
+                        .and_then(|stats| Local.timestamp_opt(stats.last_applied, 0).single().map(|date| (stats.count, date)));
+                    match last_applied {
+                        Some((1, date)) => ui.label(format!("Applied once, on {}", date.format("%Y-%m-%d"))),
+                        Some((count, date)) => ui.label(format!("Applied {} times, last on {}", count, date.format("%Y-%m-%d"))),
+                        None => ui.label(RichText::new("Never applied").weak()),
+                    };
+                    if !self.selected_mod.changelog.is_empty() {
+                        egui::CollapsingHeader::new("Changelog").show(ui, |ui| {
+                            egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                                ui.label(&self.selected_mod.changelog);
+                            });
+                        });
+                    }
+                    if !self.selected_mod.previews.is_empty() {
+                        ui.separator();
+                        ui.label(RichText::new("Previews").strong());
+                        egui::ScrollArea::horizontal().id_source("previews_scroll").show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                for path in self.selected_mod.previews.clone() {
+                                    if let Some(texture) = self.preview_texture(ctx, &path) {
+                                        let size = fit_size(texture.size_vec2(), egui::vec2(128., 128.));
+                                        let response = ui.add(egui::ImageButton::new(texture.id(), size))
+                                            .on_hover_text(path.file_name().unwrap_or_default().to_string_lossy());
+                                        if response.clicked() {
+                                            self.preview_open = Some(path);
+                                        }
+                                    }
+                                }
+                            });
+                        });
+                    }
                 });
         });
     
+        let order_before = self.order_snapshot();
         let mut config_needs_update = false;
         let mut edit_flag = false;
     
         egui::CentralPanel::default().show(ctx, |ui| {
-            let mods_return_value = self.mods_layout(ui);
-            config_needs_update = mods_return_value.0;
+            ui.horizontal(|ui| {
+                ui.label("🔍");
+                ui.add(egui::TextEdit::singleline(&mut self.search_query).hint_text("Search mods"));
+                if !self.search_query.is_empty() && ui.small_button("✖").clicked() {
+                    self.search_query.clear();
+                }
+                ui.separator();
+                let enabled_count = self.mod_datas.iter().filter(|mod_data| mod_data.enabled).count();
+                let disabled_count = self.mod_datas.len() - enabled_count;
+                ui.selectable_value(&mut self.state_filter, StateFilter::All, format!("All ({})", self.mod_datas.len()));
+                ui.selectable_value(&mut self.state_filter, StateFilter::Enabled, format!("Enabled ({})", enabled_count));
+                ui.selectable_value(&mut self.state_filter, StateFilter::Disabled, format!("Disabled ({})", disabled_count));
+                let empty_count = self.mod_datas.iter().filter(|mod_data| mod_data.empty).count();
+                if empty_count > 0 || self.state_filter == StateFilter::Empty {
+                    ui.selectable_value(&mut self.state_filter, StateFilter::Empty, format!("Empty ({})", empty_count));
+                }
+                let scripts_count = self.mod_datas.iter().filter(|mod_data| !mod_data.scripts.is_empty()).count();
+                ui.checkbox(&mut self.scripts_only, format!("Has scripts only ({})", scripts_count))
+                    .on_hover_text("Only show mods that add UnrealScript packages to +NativePackages.");
+                ui.separator();
+                if ui.button("Enable all").clicked() {
+                    let mut skipped = 0;
+                    for mod_data in &mut self.mod_datas {
+                        match mod_data.exclude_from_bulk {
+                            true => skipped += 1,
+                            false => mod_data.enabled = true,
+                        }
+                    }
+                    self.log.add_to_log(LogType::Info, format!("Enabled all mods. Skipped {} excluded mod(s).", skipped));
+                    config_needs_update = true;
+                }
+                if ui.button("Disable all").clicked() {
+                    for mod_data in &mut self.mod_datas {
+                        mod_data.enabled = false;
+                    }
+                    self.log.add_to_log(LogType::Info, "Disabled all mods.".to_owned());
+                    config_needs_update = true;
+                }
+            });
+            ui.separator();
+            let filtered = !self.search_query.trim().is_empty() || self.state_filter != StateFilter::All || self.scripts_only;
+            let mods_return_value = match (self.table_view, self.grouped_view) {
+                _ if filtered => egui::ScrollArea::vertical().show(ui, |ui| self.mods_search(ui)).inner,
+                (true, _) => self.mods_table(ui),
+                (false, true) => egui::ScrollArea::vertical().show(ui, |ui| self.mods_grouped(ui)).inner,
+                (false, false) => self.mods_layout(ui),
+            };
+            config_needs_update |= mods_return_value.0;
             edit_flag = mods_return_value.1;
         });
     
+        let reload_requested = std::mem::take(&mut WINDOW.lock().unwrap().reload_requested);
+        if reload_requested {
+            self.reload_mod(self.selected_mod.path.clone());
+        }
+        let edit_metadata_requested = std::mem::take(&mut WINDOW.lock().unwrap().edit_metadata_requested);
+        if edit_metadata_requested {
+            self.edit_metadata(self.selected_mod.path.clone());
+        }
+        let compare_requested = std::mem::take(&mut WINDOW.lock().unwrap().compare_requested);
+        if compare_requested {
+            self.compare_with_vanilla(&self.selected_mod.clone());
+        }
+        let bulk_exclude_toggled = std::mem::take(&mut WINDOW.lock().unwrap().bulk_exclude_toggled);
+        if bulk_exclude_toggled {
+            let mut config = CONFIG.lock().unwrap();
+            config.config.set_bulk_excluded(&mod_folder_name(&self.selected_mod), !self.selected_mod.exclude_from_bulk);
+            self.write_config(&mut config);
+            self.sync_mod_settings(&config);
+        }
+        let export_requested = std::mem::take(&mut WINDOW.lock().unwrap().export_requested);
+        if export_requested {
+            self.export_mod(&self.selected_mod.clone());
+        }
+        let known_good_toggled = std::mem::take(&mut WINDOW.lock().unwrap().known_good_toggled);
+        if known_good_toggled {
+            let mut config = CONFIG.lock().unwrap();
+            config.config.set_known_good(&mod_folder_name(&self.selected_mod), !self.selected_mod.known_good);
+            self.write_config(&mut config);
+            self.sync_mod_settings(&config);
+        }
+        let move_request = WINDOW.lock().unwrap().move_request.take();
+        if let Some(action) = move_request {
+            config_needs_update |= self.move_mod(&self.selected_mod.path.clone(), action);
+        }
+    
         let mut selected_index: usize = usize::MAX;
         for (index, data) in self.mod_datas.iter().enumerate() {
-            if data.name == self.selected_mod.name {
+            if data.path == self.selected_mod.path {
                 selected_index = index;
                 break;
             }
@@ -919,10 +4027,22 @@ impl eframe::App for ManagerState {
         }
     
         if config_needs_update {
+            self.enforce_enabled_limit(&order_before);
+            if self.order_snapshot() != order_before {
+                self.push_order_undo(order_before);
+            }
             let mut config = CONFIG.lock().unwrap();
             self.set_mod_order_config(&mut config);
             self.write_config(&mut config)
         }
+        else if !ctx.wants_keyboard_input() {
+            if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z)) {
+                self.undo_order();
+            }
+            else if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Y)) {
+                self.redo_order();
+            }
+        }
     
         let mut window = WINDOW.lock().unwrap();
         let mut create_open: bool = window.create_open;
@@ -931,6 +4051,24 @@ impl eframe::App for ManagerState {
         .open(&mut create_open)
         .show(ctx, |ui| {
             ui.label(RichText::new("Fill out details about your mod.").size(18.));
+
+            ui.label("Template");
+            let mut create_template = self.create_template;
+            egui::ComboBox::from_id_source("create_template")
+                .selected_text(self.mod_templates.get(create_template).map_or("Empty", |template| template.name.as_str()))
+                .show_ui(ui, |ui| {
+                    for (index, template) in self.mod_templates.iter().enumerate() {
+                        ui.selectable_value(&mut create_template, index, &template.name);
+                    }
+                });
+            if create_template != self.create_template {
+                self.create_template = create_template;
+                if let Some(template) = self.mod_templates.get(create_template) {
+                    self.mod_edit.category = template.category.clone();
+                    self.mod_edit.scripts = template.scripts.clone();
+                }
+            }
+            ui.end_row();
     
             ui.label("Name");
             ui.text_edit_singleline(&mut self.mod_edit.name);
@@ -952,10 +4090,12 @@ impl eframe::App for ManagerState {
             ui.text_edit_singleline(&mut self.mod_edit.description);
             ui.end_row();
     
+            ui.label("Changelog");
+            ui.text_edit_multiline(&mut self.mod_edit.changelog);
+            ui.end_row();
+    
             ui.label("UnrealScript Packages");
-            for script in &mut self.mod_edit.scripts {
-                ui.text_edit_singleline(script);
-            }
+            scripts_editor(ui, &mut self.create_scripts_dnd, &mut self.mod_edit.scripts);
             if ui.button("➕").clicked() {
                 self.mod_edit.scripts.push("".to_owned());
             }
@@ -964,6 +4104,11 @@ impl eframe::App for ManagerState {
             }
             ui.end_row();
     
+            egui::CollapsingHeader::new("Preview mod.ini").id_source("preview_create").show(ui, |ui| {
+                let mut preview: &str = &self.mod_edit.to_ini_string();
+                ui.add(egui::TextEdit::multiline(&mut preview).code_editor().desired_width(f32::INFINITY));
+            });
+
             let ok_response = ui.button("OK");
             let error_id = ui.make_persistent_id("error");
     
@@ -997,12 +4142,18 @@ impl eframe::App for ManagerState {
                         self.mod_edit.order = self.mod_datas.len();
                         self.mod_edit.path = Path::join(&self.mods_path, &self.mod_edit.name);
                         let final_mod: ModData = self.mod_edit.clone();
-                        match self.mod_edit.write_data() {
+                        match self.mod_edit.write_data(self.write_mod_json) {
                             Ok(()) => {
+                                if let Some(template) = self.mod_templates.get(self.create_template) {
+                                    if let Err(e) = template.scaffold(&final_mod.path) {
+                                        self.log.add_to_log(LogType::Warn, format!("Could not set up template {} for mod {}! {}", template.name, &final_mod.name, e));
+                                    }
+                                }
                                 let mut config = CONFIG.lock().unwrap();
                                 self.log.add_to_log(LogType::Info, format!("Created mod {}!", &final_mod.name));
                                 self.mod_datas.push(final_mod.clone());
                                 self.set_mod_order_config(&mut config);
+                                self.refresh_requested = true;
                                 window.create_open = false;
                                 open::that(final_mod.path.clone()).unwrap_or_default();
                             },
@@ -1022,11 +4173,21 @@ impl eframe::App for ManagerState {
         window.create_open &= create_open;
     
         let mut edit_open: bool = window.edit_open;
+        let edit_modified = match self.mod_datas.get(selected_index) {
+            Some(stored) => !self.mod_edit.metadata_eq(stored),
+            None => false,
+        };
     
         egui::Window::new("Edit Mod")
         .open(&mut edit_open)
         .show(ctx, |ui| {
-            ui.label(RichText::new("Fill out details about your mod.").size(18.));
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Fill out details about your mod.").size(18.));
+                if edit_modified {
+                    ui.label(RichText::new("● Modified").color(ui.visuals().warn_fg_color))
+                        .on_hover_text("These changes have not been saved to mod.ini yet.");
+                }
+            });
     
             ui.label("Name");
             ui.text_edit_singleline(&mut self.mod_edit.name);
@@ -1048,10 +4209,12 @@ impl eframe::App for ManagerState {
             ui.text_edit_singleline(&mut self.mod_edit.description);
             ui.end_row();
     
+            ui.label("Changelog");
+            ui.text_edit_multiline(&mut self.mod_edit.changelog);
+            ui.end_row();
+    
             ui.label("UnrealScript Packages");
-            for script in &mut self.mod_edit.scripts {
-                ui.text_edit_singleline(script);
-            }
+            scripts_editor(ui, &mut self.edit_scripts_dnd, &mut self.mod_edit.scripts);
             if ui.button("➕").clicked() {
                 self.mod_edit.scripts.push("".to_owned());
             }
@@ -1060,6 +4223,11 @@ impl eframe::App for ManagerState {
             }
             ui.end_row();
     
+            egui::CollapsingHeader::new("Preview mod.ini").id_source("preview_edit").show(ui, |ui| {
+                let mut preview: &str = &self.mod_edit.to_ini_string();
+                ui.add(egui::TextEdit::multiline(&mut preview).code_editor().desired_width(f32::INFINITY));
+            });
+
             let ok_response = ui.button("OK");
             let error_id = ui.make_persistent_id("error_edit");
     
@@ -1090,22 +4258,31 @@ impl eframe::App for ManagerState {
                         self.log.add_to_log(LogType::Error, "A mod with that name already exists!".to_owned());
                     }
                     else {
-                        self.mod_edit.path = Path::join(&self.mods_path, &self.mod_edit.name);
-                        match fs::rename(self.mod_datas[selected_index].path.clone(), self.mod_edit.path.clone())
+                        // Only a new Name moves the mod, so a mod installed as "Foo (2)" keeps its folder otherwise.
+                        if self.mod_edit.name != self.mod_datas[selected_index].name {
+                            self.mod_edit.path = Path::join(&self.mods_path, &self.mod_edit.name);
+                        }
+                        let moved = match self.mod_edit.path == self.mod_datas[selected_index].path {
+                            true => Ok(()),
+                            false => fs::rename(self.mod_datas[selected_index].path.clone(), self.mod_edit.path.clone()),
+                        };
+                        match moved
                         {
                             Ok(_) => {
                                 let final_mod: ModData = self.mod_edit.clone();
-                                match self.mod_edit.write_data() {
+                                match self.mod_edit.write_data(self.write_mod_json) {
                                     Ok(()) => {
-                                        if final_mod.name != self.mod_datas[selected_index].name {
-                                            let mut config = CONFIG.lock().unwrap();
-                                            remove_mod_config(self.mod_datas[selected_index].name.clone());
-                                            self.write_config(&mut config);
-                                            self.mod_datas[selected_index] = final_mod;
-                                            self.log.add_to_log(LogType::Info, "Mod updated!".to_owned());
-                                            self.set_mod_order_config(&mut config);
-                                            window.edit_open = false;
+                                        let renamed = final_mod.path != self.mod_datas[selected_index].path;
+                                        let mut config = CONFIG.lock().unwrap();
+                                        if renamed {
+                                            config.config.rename_mod(&mod_folder_name(&self.mod_datas[selected_index]), &mod_folder_name(&final_mod));
                                         }
+                                        self.selected_mod = final_mod.clone();
+                                        self.mod_datas[selected_index] = final_mod;
+                                        self.log.add_to_log(LogType::Info, "Mod updated!".to_owned());
+                                        self.set_mod_order_config(&mut config);
+                                        self.refresh_requested = true;
+                                        window.edit_open = false;
                                     },
                                     Err(e) => 
                                     {
@@ -1123,8 +4300,515 @@ impl eframe::App for ManagerState {
             }
         });
     
+        if !edit_open && edit_modified && window.edit_open {
+            self.edit_confirm_close = true;
+            edit_open = true;
+        }
         window.edit_open &= edit_open;
+
+        if self.edit_confirm_close {
+            let mut discard = false;
+            let mut keep_editing = false;
+            egui::Window::new("Unsaved Changes")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(format!("You have unsaved changes to {}. Discard them?", self.selected_mod.name));
+                ui.horizontal(|ui| {
+                    discard = ui.button("Discard").clicked();
+                    keep_editing = ui.button("Keep Editing").clicked();
+                });
+            });
+            if discard {
+                window.edit_open = false;
+                self.log.add_to_log(LogType::Info, format!("Discarded unsaved changes to mod {}.", self.selected_mod.name));
+            }
+            self.edit_confirm_close &= !(discard || keep_editing);
+        }
     
+        if self.poll_download() {
+            self.url_input.clear();
+            window.url_open = false;
+        }
+
+        self.poll_page_check();
+
+        self.poll_apply();
+        if let Some(running) = &self.running_apply {
+            let progress = running.task.progress();
+            // Small applies finish within a few frames, so only show progress for large ones.
+            if progress.total >= APPLY_PROGRESS_MIN_BYTES {
+                egui::Window::new("Applying Mods")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label(format!("Copying {}", progress.current));
+                    ui.add(egui::ProgressBar::new(progress.copied as f32 / progress.total as f32).show_percentage());
+                    ui.label(format!("{} of {}", helpers::format_size(progress.copied), helpers::format_size(progress.total)));
+                });
+            }
+            ctx.request_repaint();
+        }
+
+        if let Some(task) = &self.page_check_task {
+            egui::Window::new("Checking Mod Pages")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let (checked, total) = task.progress();
+                let fraction = match total {
+                    0 => 1.,
+                    _ => checked as f32 / total as f32,
+                };
+                ui.add(egui::ProgressBar::new(fraction).text(format!("{} / {}", checked, total)));
+                ctx.request_repaint();
+            });
+        }
+
+        if let Some(url) = &self.clipboard_url {
+            let mut install = false;
+            let mut dismiss = false;
+            egui::Window::new("Install from Clipboard")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10., -40.))
+            .show(ctx, |ui| {
+                ui.label("Your clipboard contains a mod link:");
+                ui.label(RichText::new(url).weak());
+                ui.horizontal(|ui| {
+                    install = ui.add_enabled(self.download_task.is_none(), egui::Button::new("Install")).clicked();
+                    dismiss = ui.button("Dismiss").clicked();
+                });
+            });
+            if install {
+                self.log.add_to_log(LogType::Info, format!("Downloading mod from {}...", url));
+                self.download_task = Some(DownloadTask::start(url.clone(), self.download_settings.clone()));
+                window.url_open = true;
+                self.clipboard_url = None;
+            }
+            else if dismiss {
+                self.clipboard_url = None;
+            }
+        }
+
+        let mut url_open: bool = window.url_open;
+
+        egui::Window::new("Install from URL")
+        .open(&mut url_open)
+        .show(ctx, |ui| {
+            match &self.download_task {
+                Some(task) => {
+                    ui.label(format!("Downloading {}", task.url));
+                    let progress = task.progress();
+                    let progress_bar = match progress.total {
+                        Some(total) if total > 0 => egui::ProgressBar::new(progress.downloaded as f32 / total as f32).show_percentage(),
+                        _ => egui::ProgressBar::new(0.).text(format!("{} bytes", progress.downloaded)),
+                    };
+                    ui.add(progress_bar);
+                    match self.download_settings.max_bytes_per_sec {
+                        Some(limit) => ui.label(format!("{}/s (limited to {}/s)", helpers::format_size(progress.bytes_per_sec), helpers::format_size(limit))),
+                        None => ui.label(format!("{}/s", helpers::format_size(progress.bytes_per_sec))),
+                    };
+                    ctx.request_repaint();
+                }
+                None => {
+                    ui.label("Paste a direct download link to a mod archive.");
+                    ui.text_edit_singleline(&mut self.url_input);
+
+                    let install_response = ui.button("Install");
+                    let error_id = ui.make_persistent_id("error_url");
+
+                    egui::popup::popup_below_widget(ui, error_id, &install_response, |ui| {
+                        ui.set_min_width(150.);
+                        ui.label("Invalid URL! Check log for more details.");
+                    });
+
+                    if install_response.clicked() {
+                        match download::validate_url(&self.url_input) {
+                            Ok(url) => {
+                                self.log.add_to_log(LogType::Info, format!("Downloading mod from {}...", url));
+                                self.download_task = Some(DownloadTask::start(url.to_string(), self.download_settings.clone()));
+                            }
+                            Err(e) => {
+                                ui.memory_mut(|mem|{
+                                    mem.toggle_popup(error_id);
+                                });
+                                self.log.add_to_log(LogType::Error, format!("Could not download mod! {}", e))
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        window.url_open &= url_open;
+
+        if let Some(pending) = &self.pending_reinstall {
+            let mut proceed = false;
+            let mut cancel = false;
+            egui::Window::new("Update Mod")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(format!("{} is already installed.", pending.name)).size(18.));
+                match pending.diff.is_empty() {
+                    true => {
+                        ui.label("The new archive is identical to the installed version.");
+                    }
+                    false => {
+                        ui.label(format!("{} file(s) added, {} changed, {} removed.",
+                            pending.diff.added.len(), pending.diff.changed.len(), pending.diff.removed.len()));
+                        egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                            for file in &pending.diff.added {
+                                ui.label(RichText::new(format!("+ {}", file.display())).color(Color32::GREEN));
+                            }
+                            for file in &pending.diff.changed {
+                                ui.label(RichText::new(format!("~ {}", file.display())).color(Color32::YELLOW));
+                            }
+                            for file in &pending.diff.removed {
+                                ui.label(RichText::new(format!("- {}", file.display())).color(Color32::RED));
+                            }
+                        });
+                    }
+                }
+                ui.horizontal(|ui| {
+                    proceed = ui.button("Update").clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+            if proceed {
+                let pending = self.pending_reinstall.take().unwrap();
+                let mut config = CONFIG.lock().unwrap();
+                self.finish_reinstall(pending, &mut config);
+                self.set_mod_order_config(&mut config);
+            }
+            else if cancel {
+                let pending = self.pending_reinstall.take().unwrap();
+                self.log.add_to_log(LogType::Info, format!("Cancelled update of mod {}.", pending.name));
+            }
+        }
+
+        if let Some(pending) = &mut self.pending_restore {
+            let mut restore = false;
+            let mut cancel = false;
+            egui::Window::new("Restore Mods Backup")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(format!("Select the mods to restore from {}.", pending.path.display()));
+                ui.label("Restored mods replace files in existing folders with the same name.");
+                ui.horizontal(|ui| {
+                    if ui.button("Select all").clicked() {
+                        pending.entries.iter_mut().for_each(|(_, selected)| *selected = true);
+                    }
+                    if ui.button("Select none").clicked() {
+                        pending.entries.iter_mut().for_each(|(_, selected)| *selected = false);
+                    }
+                });
+                egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                    for (entry, selected) in &mut pending.entries {
+                        let label = match (entry.name == entry.folder, entry.version.is_empty()) {
+                            (true, true) => entry.folder.clone(),
+                            (true, false) => format!("{} {}", entry.folder, entry.version),
+                            (false, true) => format!("{} ({})", entry.name, entry.folder),
+                            (false, false) => format!("{} {} ({})", entry.name, entry.version, entry.folder),
+                        };
+                        ui.checkbox(selected, label);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    restore = ui.button("Restore").clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+            if restore {
+                let pending = self.pending_restore.take().unwrap();
+                self.restore_backup(pending);
+            }
+            else if cancel {
+                self.pending_restore = None;
+            }
+        }
+
+        if let Some(path) = self.pending_mods_path.clone() {
+            let mut choice: Option<bool> = None;
+            let mut cancel = false;
+            egui::Window::new("Change Mods Folder")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(format!("Move existing mods from {} to {}?", self.mods_path.display(), path.display()));
+                ui.horizontal(|ui| {
+                    if ui.button("Move mods").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Don't move").clicked() {
+                        choice = Some(false);
+                    }
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+            if let Some(move_mods) = choice {
+                self.pending_mods_path = None;
+                self.set_mods_path(path, move_mods);
+            }
+            else if cancel {
+                self.pending_mods_path = None;
+            }
+        }
+
+        if let Some(release) = &self.available_update {
+            let mut update = false;
+            let mut later = false;
+            egui::Window::new("Update Available")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(format!("Version {} is available!", release.version)).size(18.));
+                ui.label(format!("You are on version {}.", cargo_crate_version!()));
+                ui.label("The mod manager will save your settings and restart after updating.");
+                if let Some(body) = &release.body {
+                    egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                        ui.label(body);
+                    });
+                }
+                ui.horizontal(|ui| {
+                    update = ui.button("Update now").clicked();
+                    later = ui.button("Later").clicked();
+                });
+            });
+            if update {
+                self.available_update = None;
+                self.run_update();
+            }
+            else if later {
+                self.available_update = None;
+            }
+        }
+
+        if let Some(report) = &self.vanilla_report {
+            let mut close = false;
+            egui::Window::new("Compare with Game Files")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().max_height(400.).show(ui, |ui| {
+                    let mut report: &str = report;
+                    ui.add(egui::TextEdit::multiline(&mut report).code_editor().desired_width(f32::INFINITY));
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Copy").clicked() {
+                        ui.output_mut(|o| o.copied_text = report.clone());
+                    }
+                    close = ui.button("Close").clicked();
+                });
+            });
+            if close {
+                self.vanilla_report = None;
+            }
+        }
+
+        if let Some(error) = &self.mods_dir_error {
+            let mut change = false;
+            egui::Window::new("Mods Folder Unavailable")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(error).color(ui.visuals().error_fg_color));
+                change = ui.button("Change Mods Folder").clicked();
+            });
+            if change {
+                if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                    match helpers::ensure_writable_dir(&path) {
+                        Ok(_) => self.set_mods_path(path, false),
+                        Err(e) => self.log.add_to_log(LogType::Error, format!("Mods folder {} is not writable! {}", path.display(), e)),
+                    }
+                }
+            }
+        }
+
+        if let Some(reason) = &self.elevation_reason {
+            let mut restart = false;
+            let mut dismiss = false;
+            egui::Window::new("Administrator Rights Needed")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(reason);
+                ui.label("Windows denied access. This usually happens when the mod manager is installed in a protected folder such as Program Files.");
+                ui.horizontal(|ui| {
+                    restart = ui.button("Restart as administrator").clicked();
+                    dismiss = ui.button("Dismiss").clicked();
+                });
+            });
+            if restart {
+                self.restart_as_admin();
+            }
+            else if dismiss {
+                self.elevation_reason = None;
+            }
+        }
+
+        if let Some(report) = &self.incompatibility_report {
+            let mut close = false;
+            egui::Window::new("Incompatible Mods")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(report).color(ui.visuals().error_fg_color));
+                close = ui.button("OK").clicked();
+            });
+            if close {
+                self.incompatibility_report = None;
+            }
+        }
+
+        if let Some(mod_path) = &self.editing_metadata {
+            let mut reload = false;
+            let mut close = false;
+            egui::Window::new("Editing Metadata")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("{} is open in your text editor.", ModData::metadata_file(mod_path).display()));
+                ui.label("Save it there, then reload to see your changes.");
+                ui.horizontal(|ui| {
+                    reload = ui.button("Reload").clicked();
+                    close = ui.button("Close").clicked();
+                });
+            });
+            if reload {
+                self.reload_mod(mod_path.clone());
+                self.editing_metadata = None;
+            }
+            else if close {
+                self.editing_metadata = None;
+            }
+        }
+
+        if let Some(leftovers) = &self.clean_check {
+            let mut close = false;
+            let mut fix = false;
+            egui::Window::new("Clean Uninstall Check")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                if leftovers.is_empty() {
+                    ui.label("The game is clean. No mods are applied and only the base REDGame script package is loaded.");
+                }
+                else {
+                    ui.label(RichText::new("The game is not clean:").color(ui.visuals().warn_fg_color));
+                    for leftover in leftovers {
+                        ui.label(format!("• {}", leftover));
+                    }
+                    ui.label("Fixing removes the game's Mods folder and resets the script packages. Your mods and load order in the mod manager are kept.");
+                }
+                ui.horizontal(|ui| {
+                    if !leftovers.is_empty() {
+                        fix = ui.button("Fix").clicked();
+                    }
+                    close = ui.button("Close").clicked();
+                });
+            });
+            if fix {
+                self.clean_install();
+            }
+            else if close {
+                self.clean_check = None;
+            }
+        }
+
+        if let Some(results) = &self.install_report {
+            let mut close = false;
+            egui::Window::new("Install Summary")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+                ui.label(format!("Installed {} of {} archives.", results.len() - failed, results.len()));
+                egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                    egui::Grid::new("install_report").striped(true).show(ui, |ui| {
+                        for (path, result) in results {
+                            let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+                            match result {
+                                Ok(()) => {
+                                    ui.label(RichText::new("✔").color(ui.visuals().hyperlink_color));
+                                    ui.label(file_name);
+                                    ui.label("");
+                                }
+                                Err(reason) => {
+                                    ui.label(RichText::new("✖").color(ui.visuals().error_fg_color));
+                                    ui.label(file_name);
+                                    ui.label(RichText::new(reason).color(ui.visuals().error_fg_color));
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+                close = ui.button("OK").clicked();
+            });
+            if close {
+                self.install_report = None;
+            }
+        }
+
+        if let Some(report) = &self.import_report {
+            let mut close = false;
+            egui::Window::new("Mod List Import")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                    ui.label(report);
+                });
+                close = ui.button("OK").clicked();
+            });
+            if close {
+                self.import_report = None;
+            }
+        }
+
+        if let Some(report) = &self.config_recovery_report {
+            let mut close = false;
+            egui::Window::new("Config Recovered")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(report);
+                close = ui.button("OK").clicked();
+            });
+            if close {
+                self.config_recovery_report = None;
+            }
+        }
+
+        self.poll_launch_watch();
+        if self.launch_watch.is_some() {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+
+        if self.crash_prompt_open {
+            let mut open_logs = false;
+            let mut dismiss = false;
+            egui::Window::new("Game Closed")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label("Guilty Gear Xrd closed quickly after launching. A mod may have crashed the game.");
+                ui.label("Open game logs?");
+                ui.horizontal(|ui| {
+                    open_logs = ui.button("Open Logs").clicked();
+                    dismiss = ui.button("Dismiss").clicked();
+                });
+            });
+            if open_logs {
+                self.open_game_logs();
+            }
+            self.crash_prompt_open &= !(open_logs || dismiss);
+        }
+
+        if let Some(path) = self.preview_open.clone() {
+            let mut preview_open = true;
+            egui::Window::new(path.file_name().unwrap_or_default().to_string_lossy())
+            .id(egui::Id::new("preview_window"))
+            .open(&mut preview_open)
+            .show(ctx, |ui| {
+                if let Some(texture) = self.preview_texture(ctx, &path) {
+                    let max = ctx.screen_rect().size() * 0.8;
+                    ui.image(texture.id(), fit_size(texture.size_vec2(), max));
+                }
+            });
+            if !preview_open {
+                self.preview_open = None;
+            }
+        }
+
         let mut remove_open: bool = window.remove_open;
         
         egui::Window::new("Remove Mod")
@@ -1139,11 +4823,11 @@ impl eframe::App for ManagerState {
                     match fs::remove_dir_all(self.mod_datas[selected_index].path.clone())
                     {
                         Ok(_) => {
-                            remove_mod_config(self.mod_datas[selected_index].name.clone());
+                            remove_mod_config(mod_folder_name(&self.mod_datas[selected_index]));
+                            self.mod_datas.remove(selected_index);
+                            self.refresh_requested = true;
                             let mut config = CONFIG.lock().unwrap();
                             self.set_mod_order_config(&mut config);
-                            self.write_config(&mut config);
-                            self.mod_datas.remove(selected_index);
                             window.remove_open = false;
                         }
                         Err(e) => self.log.add_to_log(LogType::Error, format!("Could not remove mod! {}", e)),
@@ -1157,6 +4841,80 @@ impl eframe::App for ManagerState {
         
         window.remove_open &= remove_open;
     
+        egui::Window::new("Diagnostics")
+        .open(&mut window.diagnostics_open)
+        .show(ctx, |ui| {
+            let mut report: &str = &self.diagnostics_report;
+            ui.add(
+                egui::TextEdit::multiline(&mut report)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(f32::INFINITY),
+            );
+            if ui.button("Copy to clipboard").clicked() {
+                ui.output_mut(|o| o.copied_text = self.diagnostics_report.clone());
+            }
+        });
+
+        egui::Window::new("Launch Options")
+        .open(&mut window.launch_options_open)
+        .show(ctx, |ui| {
+            ui.label("Some mods need launch options set in Steam. Paste these into the game's launch options from Tools > Open Steam Properties.");
+            ui.horizontal(|ui| {
+                ui.label("Recommended:");
+                ui.label(RichText::new(RECOMMENDED_LAUNCH_OPTIONS).monospace());
+                if ui.button("Copy").clicked() {
+                    ui.output_mut(|o| o.copied_text = RECOMMENDED_LAUNCH_OPTIONS.to_owned());
+                }
+                if ui.button("Use").clicked() {
+                    self.launch_options = RECOMMENDED_LAUNCH_OPTIONS.to_owned();
+                }
+            });
+            ui.text_edit_singleline(&mut self.launch_options);
+            let (args, conflicts) = launch_args(&self.launch_options, &self.mod_datas);
+            ui.label("With the Direct launch method, launch arguments from enabled mods are added as well:");
+            ui.label(RichText::new(args.join(" ")).monospace());
+            for conflict in &conflicts {
+                ui.label(RichText::new(conflict).color(ui.visuals().warn_fg_color));
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Copy to clipboard").clicked() {
+                    ui.output_mut(|o| o.copied_text = self.launch_options.clone());
+                }
+                if ui.button("Save").clicked() {
+                    let mut config = CONFIG.lock().unwrap();
+                    config.config.general.launch_options = self.launch_options.clone();
+                    self.write_config(&mut config);
+                }
+            });
+        });
+
+        egui::Window::new("Console Colors")
+        .open(&mut window.console_colors_open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let (info_color, warn_color, error_color, _) = console_colors(self.high_contrast, ctx.style().visuals.dark_mode);
+            let defaults = [info_color, warn_color, error_color];
+            let mut changed = false;
+            egui::Grid::new("console_colors").show(ui, |ui| {
+                for (index, label) in ["Info", "Warn", "Error"].into_iter().enumerate() {
+                    ui.label(label);
+                    let mut color = self.console_color_overrides[index].unwrap_or(defaults[index]);
+                    if ui.color_edit_button_srgba(&mut color).changed() {
+                        self.console_color_overrides[index] = Some(color);
+                        changed = true;
+                    }
+                    if ui.add_enabled(self.console_color_overrides[index].is_some(), egui::Button::new("Reset")).clicked() {
+                        self.console_color_overrides[index] = None;
+                        changed = true;
+                    }
+                    ui.end_row();
+                }
+            });
+            if changed {
+                self.save_console_colors();
+            }
+        });
+
         egui::Window::new("About")
         .open(&mut window.about_open)
         .show(ctx, |ui| {
@@ -1164,21 +4922,103 @@ impl eframe::App for ManagerState {
             ui.label(format!("Version {}", cargo_crate_version!()))
         });
 
-        self.update_mods();
+        self.refresh_mods(ctx);
+        self.flush_config_debounced(ctx);
     }
 
     fn on_close_event(&mut self) -> bool {
+        // Let a running apply finish, so the game is not left with half-copied mods, but don't launch anything.
+        if let Some(running) = self.running_apply.take() {
+            self.log.add_to_log(LogType::Info, "Waiting for mods to finish copying before closing.".to_owned());
+            self.finish_apply(running, true);
+        }
         let mut config = CONFIG.lock().unwrap();
         self.set_mod_order_config(&mut config);
         self.write_config(&mut config);
+        self.flush_config(&mut config);
+        if self.close_to_tray && self.tray.is_some() && !self.quit_requested {
+            self.hide_to_tray = true;
+            return false
+        }
         true
     }        
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-fn help_menu(ui: &mut Ui)
-{
-    if ui.button("About").clicked() {
-        WINDOW.lock().unwrap().about_open = true;
-        ui.close_menu();
+    fn mod_data(name: &str) -> ModData {
+        ModData {
+            name: name.to_string(),
+            author: "作者".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn mod_folder_keeps_kanji_names() {
+        let manager = ManagerState::default();
+        let folder = manager.mod_folder(0, 2, &mod_data("ソル=バッドガイ"));
+        assert_eq!(folder, PathBuf::from("000").join("ソル=バッドガイ"));
+
+        let manager = ManagerState {
+            folder_template: "{author}/{name}".to_string(),
+            ..Default::default()
+        };
+        let folder = manager.mod_folder(0, 1, &mod_data("闇慈"));
+        assert_eq!(folder, PathBuf::from("作者").join("闇慈"));
+    }
+
+    #[test]
+    fn mod_folders_sort_in_load_order() {
+        let manager = ManagerState::default();
+        let folders: Vec<PathBuf> = (0..12)
+            .map(|index| manager.mod_folder(index, 12, &mod_data("梅喧")))
+            .collect();
+        let mut sorted = folders.clone();
+        sorted.sort();
+        assert_eq!(folders, sorted);
+    }
+
+    fn write_mod_archive(path: &Path, name: &str) {
+        use std::io::Write;
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+        let options = zip::write::FileOptions::default();
+        zip.start_file("mod.ini", options).unwrap();
+        write!(zip, "[Description]\nName={}\n", name).unwrap();
+        zip.start_file("REDGame/CookedPCConsole/file.upk", options).unwrap();
+        zip.write_all(b"data").unwrap();
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn renamed_installs_survive_a_rescan() {
+        let dir = tempfile::tempdir().unwrap();
+        let mods_path = dir.path().join("Mods");
+        let first = dir.path().join("first").join("Foo.zip");
+        let second = dir.path().join("second").join("Foo.zip");
+        write_mod_archive(&first, "Foo");
+        write_mod_archive(&second, "Foo");
+        // Marking the config dirty keeps update_mods from loading config.ini next to the test binary.
+        let mut manager = ManagerState {
+            interactive: true,
+            config_dirty: true,
+            custom_mods_path: Some(mods_path.clone()),
+            mods_path,
+            collision_policy: CollisionPolicy::Rename,
+            ..Default::default()
+        };
+        let mut config = CONFIG.lock().unwrap();
+        config.config = Config::default();
+        config.config.general.mods_keyed_by_folder = true;
+        manager.install_mod(first, &mut config).unwrap();
+        manager.install_mod(second, &mut config).unwrap();
+        drop(config);
+
+        manager.update_mods();
+        let folders: Vec<String> = manager.mod_datas.iter().map(mod_folder_name).collect();
+        assert_eq!(folders, ["Foo", "Foo (2)"]);
+        assert!(manager.mod_datas.iter().all(|mod_data| mod_data.name == "Foo" && mod_data.duplicate_name));
     }
-}
\ No newline at end of file
+}
@@ -1,19 +1,60 @@
 use std::{path::{PathBuf, Path}, fs};
-use ini::Ini;
+use ini::{Ini, Properties};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use crate::{archive, config::{ApplyStats, Schedule}, helpers};
 use std::hash::{Hash, Hasher};
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize)]
 pub struct ModData {
     pub name: String,
     pub author: String,
     pub version: String,
     pub category: String,
+    #[serde(skip)]
     pub description: String,
+    #[serde(skip)]
     pub page: String,
+    #[serde(skip)]
+    pub icon: String,
+    #[serde(skip)]
+    pub changelog: String,
     pub path: PathBuf,
     pub enabled: bool,
     pub order: usize,
     pub scripts: Vec<String>,
+    #[serde(skip)]
+    pub exclude: Vec<String>,
+    #[serde(skip)]
+    pub min_manager_version: String,
+    #[serde(skip)]
+    pub launch_args: Vec<String>,
+    #[serde(skip)]
+    pub incompatible_with: Vec<String>,
+    #[serde(skip)]
+    pub conflicts: Vec<String>,
+    #[serde(skip)]
+    pub page_missing: bool,
+    #[serde(skip)]
+    pub notes: String,
+    #[serde(skip)]
+    pub exclude_from_bulk: bool,
+    #[serde(skip)]
+    pub known_good: bool,
+    #[serde(skip)]
+    pub duplicate_name: bool,
+    #[serde(skip)]
+    pub installed_at: Option<i64>,
+    #[serde(skip)]
+    pub schedule: Option<Schedule>,
+    #[serde(skip)]
+    pub apply_stats: Option<ApplyStats>,
+    #[serde(skip)]
+    pub empty: bool,
+    #[serde(skip)]
+    pub packed: Option<PathBuf>,
+    #[serde(skip)]
+    pub previews: Vec<PathBuf>,
 }
 
 impl Hash for ModData {
@@ -22,6 +63,40 @@ impl Hash for ModData {
     }
 }
 
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct ModJson {
+    name: String,
+    author: String,
+    version: String,
+    category: String,
+    description: String,
+    page: String,
+    icon: String,
+    changelog: String,
+    scripts: Vec<String>,
+    exclude: Vec<String>,
+    min_manager_version: String,
+    launch_args: Vec<String>,
+    incompatible_with: Vec<String>,
+}
+
+pub enum ModIniError {
+    Read(ini::Error),
+    Json(serde_json::Error),
+    MissingDescription,
+}
+
+impl std::fmt::Display for ModIniError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModIniError::Read(e) => write!(f, "{}", e),
+            ModIniError::Json(e) => write!(f, "{}", e),
+            ModIniError::MissingDescription => write!(f, "The mod ini doesn't have a description section!"),
+        }
+    }
+}
+
 impl ModData {
     pub fn new() -> ModData {
         ModData {
@@ -31,16 +106,282 @@ impl ModData {
             category: "".to_owned(), 
             description: "".to_owned(), 
             page: "".to_owned(), 
+            icon: "".to_owned(),
+            changelog: "".to_owned(),
             path: PathBuf::new(),
             enabled: true, 
             order: 0,
             scripts: Vec::new(),
+            exclude: Vec::new(),
+            min_manager_version: "".to_owned(),
+            launch_args: Vec::new(),
+            incompatible_with: Vec::new(),
+            conflicts: Vec::new(),
+            page_missing: false,
+            notes: "".to_owned(),
+            exclude_from_bulk: false,
+            known_good: false,
+            duplicate_name: false,
+            installed_at: None,
+            schedule: None,
+            apply_stats: None,
+            empty: false,
+            packed: None,
+            previews: Vec::new(),
+        }
+    }
+
+    /// Returns the mod.ini in `folder`, or its mod.json if there is no mod.ini.
+    pub fn metadata_file(folder: &Path) -> PathBuf {
+        let ini_path = folder.join("mod.ini");
+        let json_path = folder.join("mod.json");
+        match !ini_path.exists() && json_path.exists() {
+            true => json_path,
+            false => ini_path,
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<ModData, ModIniError> {
+        match path.as_ref().extension().map_or(false, |extension| extension.eq_ignore_ascii_case("json")) {
+            true => ModData::from_json_file(path),
+            false => ModData::from_ini_file(path),
+        }
+    }
+
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<ModData, ModIniError> {
+        let text = fs::read_to_string(path).map_err(|e| ModIniError::Read(ini::Error::Io(e)))?;
+        let json: ModJson = serde_json::from_str(&text).map_err(ModIniError::Json)?;
+        let mut mod_data = ModData::new();
+        mod_data.name = json.name;
+        mod_data.author = json.author;
+        mod_data.version = json.version;
+        mod_data.category = json.category;
+        mod_data.description = json.description;
+        mod_data.page = json.page;
+        mod_data.icon = json.icon;
+        mod_data.changelog = json.changelog;
+        mod_data.scripts = json.scripts;
+        mod_data.exclude = json.exclude;
+        mod_data.min_manager_version = json.min_manager_version.trim().to_owned();
+        mod_data.launch_args = json.launch_args;
+        mod_data.incompatible_with = json.incompatible_with;
+        Ok(mod_data)
+    }
+
+    pub fn from_ini_file(path: impl AsRef<Path>) -> Result<ModData, ModIniError> {
+        let file = Ini::load_from_file_noescape(path).map_err(ModIniError::Read)?;
+        let desc = file.section(Some("Description")).ok_or(ModIniError::MissingDescription)?;
+        let get = |key: &str| desc.get(key).unwrap_or_default().to_owned();
+
+        let mut mod_data = ModData::new();
+        mod_data.name = get("Name");
+        mod_data.author = get("Author");
+        mod_data.version = get("Version");
+        mod_data.category = get("Category");
+        mod_data.description = get("Description");
+        mod_data.page = get("Page");
+        mod_data.icon = get("Icon");
+        mod_data.changelog = helpers::unescape_ini(&get("Changelog"));
+
+        if let Some(section) = file.section(Some("Scripts")) {
+            for script in section.get_all("ScriptPackage") {
+                mod_data.scripts.push(script.to_owned());
+            }
+        }
+
+        if let Some(section) = file.section(Some("Requirements")) {
+            mod_data.min_manager_version = section.get("MinManagerVersion").unwrap_or_default().trim().to_owned();
+        }
+
+        if let Some(section) = file.section(Some("Launch")) {
+            mod_data.launch_args = section.get("LaunchArgs").unwrap_or_default().split_whitespace().map(|arg| arg.to_owned()).collect();
+        }
+
+        if let Some(section) = file.section(Some("Compatibility")) {
+            mod_data.incompatible_with = section.get("IncompatibleWith").unwrap_or_default().split(',')
+                .map(|name| name.trim())
+                .filter(|name| !name.is_empty())
+                .map(|name| name.to_owned())
+                .collect();
+        }
+
+        if let Some(section) = file.section(Some("Files")) {
+            for pattern in section.get_all("Exclude") {
+                mod_data.exclude.push(pattern.to_owned());
+            }
+        }
+
+        Ok(mod_data)
+    }
+
+    pub fn metadata_eq(&self, other: &ModData) -> bool {
+        self.name == other.name
+            && self.author == other.author
+            && self.version == other.version
+            && self.category == other.category
+            && self.description == other.description
+            && self.page == other.page
+            && self.icon == other.icon
+            && self.changelog == other.changelog
+            && self.scripts == other.scripts
+            && self.exclude == other.exclude
+            && self.min_manager_version == other.min_manager_version
+            && self.launch_args == other.launch_args
+            && self.incompatible_with == other.incompatible_with
+    }
+
+    pub fn infer_from_folder(name: &str, path: &Path) -> (ModData, Vec<String>) {
+        let mut mod_data = ModData::new();
+        let mut inferred = vec![format!("Name {} from the folder name", name)];
+        mod_data.name = name.to_owned();
+        mod_data.path = path.to_path_buf();
+
+        let files = helpers::list_files(path).unwrap_or_default();
+        for file in &files {
+            let is_script = file.extension().map_or(false, |extension| extension.eq_ignore_ascii_case("u"));
+            if let (true, Some(stem)) = (is_script, file.file_stem()) {
+                let script = stem.to_string_lossy().into_owned();
+                if !mod_data.scripts.contains(&script) {
+                    inferred.push(format!("script package {} from {}", script, file.display()));
+                    mod_data.scripts.push(script);
+                }
+            }
         }
+
+        let categories = [
+            ("CHR_", "Character"),
+            ("STG_", "Stage"),
+            ("BGM", "Music"),
+            ("SND", "Sound"),
+            ("UI_", "UI"),
+            ("MENU", "UI"),
+        ];
+        let guess = categories.iter().find(|(pattern, _)| files.iter().any(|file| {
+            file.to_string_lossy().to_uppercase().contains(pattern)
+        }));
+        if let Some((pattern, category)) = guess {
+            mod_data.category = category.to_string();
+            inferred.push(format!("category {} from files containing {}", category, pattern));
+        }
+
+        (mod_data, inferred)
+    }
+
+    pub fn unmet_requirement(&self) -> Option<String> {
+        if self.min_manager_version.is_empty() {
+            return None
+        }
+        let current = Version::parse(env!("CARGO_PKG_VERSION")).ok()?;
+        match Version::parse(&self.min_manager_version) {
+            Ok(required) if required > current => Some(format!("Requires mod manager version {} or newer, but version {} is installed.", required, current)),
+            Ok(_) => None,
+            Err(e) => Some(format!("Invalid MinManagerVersion {}! {}", self.min_manager_version, e)),
+        }
+    }
+
+    pub fn is_new(&self) -> bool {
+        match self.installed_at {
+            Some(installed_at) => chrono::Utc::now().timestamp() - installed_at < 24 * 60 * 60,
+            None => false,
+        }
+    }
+
+    /// Whether this mod declares `other` as incompatible, by mod or folder name.
+    pub fn declares_incompatible(&self, other: &ModData) -> bool {
+        let folder = other.path.file_name().map(|folder| folder.to_string_lossy().into_owned()).unwrap_or_default();
+        self.incompatible_with.iter().any(|name| name.eq_ignore_ascii_case(&other.name) || name.eq_ignore_ascii_case(&folder))
     }
 
-    pub fn write_data(&self) -> std::io::Result<()> 
+    pub fn display_name(&self) -> String {
+        match (self.duplicate_name, self.path.file_name()) {
+            (true, Some(folder)) => format!("{} ({})", self.name, folder.to_string_lossy()),
+            _ => self.name.clone(),
+        }
+    }
+
+    /// The category with nesting levels separated by `/`, e.g. "Skins/Sol/Overtures".
+    pub fn category_name(&self) -> String {
+        let levels: Vec<&str> = self.category.split('/').map(|level| level.trim()).filter(|level| !level.is_empty()).collect();
+        match levels.is_empty() {
+            true => "Uncategorized".to_owned(),
+            false => levels.join("/"),
+        }
+    }
+
+    /// The icon file named by `Icon` in the metadata, relative to the mod folder.
+    pub fn icon_path(&self) -> Option<PathBuf> {
+        match self.icon.trim() {
+            "" => None,
+            icon => Some(self.path.join(icon)),
+        }
+    }
+
+    pub fn load_changelog_file(&mut self) {
+        if !self.changelog.is_empty() {
+            return
+        }
+        for file_name in ["changelog.txt", "CHANGELOG.md"] {
+            if let Ok(changelog) = fs::read_to_string(self.path.join(file_name)) {
+                self.changelog = changelog;
+                return
+            }
+        }
+    }
+
+    pub fn load_previews(&mut self) {
+        self.previews.clear();
+        for dir in [self.path.clone(), self.path.join("previews")] {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            let mut images: Vec<PathBuf> = entries.flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .filter(|path| match path.extension().and_then(|extension| extension.to_str()) {
+                    Some(extension) => ["png", "jpg", "jpeg"].contains(&extension.to_lowercase().as_str()),
+                    None => false,
+                })
+                .collect();
+            images.sort();
+            self.previews.append(&mut images);
+        }
+    }
+
+    /// Finds the archive of a mod stored compressed. Its files are only extracted when applying.
+    pub fn load_packed(&mut self) {
+        self.packed = archive::packed_archive(&self.path);
+    }
+
+    /// Flags the mod as empty when its folder holds nothing besides metadata, changelogs and previews,
+    /// which usually means an extraction was interrupted or the files were deleted by hand.
+    pub fn load_empty(&mut self) {
+        self.empty = !self.has_content(&self.path);
+    }
+
+    fn has_content(&self, dir: &Path) -> bool {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return false,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if self.has_content(&path) {
+                    return true
+                }
+                continue
+            }
+            let bookkeeping = dir == self.path && ["mod.ini", "mod.json", "changelog.txt", "CHANGELOG.md"].iter().any(|name| entry.file_name() == *name);
+            if !bookkeeping && !self.previews.contains(&path) {
+                return true
+            }
+        }
+        false
+    }
+
+    pub fn to_ini(&self) -> Ini
     {
-        fs::create_dir_all(&self.path)?;
         let mut conf = Ini::new();
         conf.with_section(Some("Description"))
             .set("Name", &self.name)
@@ -50,11 +391,83 @@ impl ModData {
             .set("Description", &self.description)
             .set("Page", &self.page);
 
-        for script in &self.scripts {
-            conf.with_section(Some("Scripts")).set("ScriptPackage", script);
+        if !self.icon.is_empty() {
+            conf.with_section(Some("Description")).set("Icon", &self.icon);
         }
 
-        conf.write_to_file(Path::join(&self.path, "mod.ini"))?;
+        if !self.changelog.is_empty() {
+            conf.with_section(Some("Description")).set("Changelog", &self.changelog);
+        }
+
+        if !self.scripts.is_empty() {
+            let section = conf.entry(Some("Scripts".to_owned())).or_insert(Properties::new());
+            for script in &self.scripts {
+                section.append("ScriptPackage", script);
+            }
+        }
+
+        if !self.min_manager_version.is_empty() {
+            conf.with_section(Some("Requirements")).set("MinManagerVersion", &self.min_manager_version);
+        }
+
+        if !self.launch_args.is_empty() {
+            conf.with_section(Some("Launch")).set("LaunchArgs", self.launch_args.join(" "));
+        }
+
+        if !self.incompatible_with.is_empty() {
+            conf.with_section(Some("Compatibility")).set("IncompatibleWith", self.incompatible_with.join(", "));
+        }
+
+        if !self.exclude.is_empty() {
+            let section = conf.entry(Some("Files".to_owned())).or_insert(Properties::new());
+            for pattern in &self.exclude {
+                section.append("Exclude", pattern);
+            }
+        }
+
+        conf
+    }
+
+    pub fn to_ini_string(&self) -> String
+    {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.to_ini().write_to(&mut buffer).unwrap_or_default();
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+
+    fn to_json_string(&self) -> serde_json::Result<String>
+    {
+        serde_json::to_string_pretty(&ModJson {
+            name: self.name.clone(),
+            author: self.author.clone(),
+            version: self.version.clone(),
+            category: self.category.clone(),
+            description: self.description.clone(),
+            page: self.page.clone(),
+            icon: self.icon.clone(),
+            changelog: self.changelog.clone(),
+            scripts: self.scripts.clone(),
+            exclude: self.exclude.clone(),
+            min_manager_version: self.min_manager_version.clone(),
+            launch_args: self.launch_args.clone(),
+            incompatible_with: self.incompatible_with.clone(),
+        })
+    }
+
+    /// Writes the metadata in the format the mod already uses. Mods without metadata get mod.json
+    /// when `prefer_json` is set, and mod.ini otherwise.
+    pub fn write_data(&self, prefer_json: bool) -> std::io::Result<()> 
+    {
+        fs::create_dir_all(&self.path)?;
+        let use_json = match (self.path.join("mod.ini").exists(), self.path.join("mod.json").exists()) {
+            (true, _) => false,
+            (false, true) => true,
+            (false, false) => prefer_json,
+        };
+        match use_json {
+            true => helpers::write_file_atomic(Path::join(&self.path, "mod.json"), self.to_json_string()?.as_bytes())?,
+            false => helpers::write_ini_atomic(&self.to_ini(), Path::join(&self.path, "mod.ini"))?,
+        }
 
         Ok(())
     }
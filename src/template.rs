@@ -0,0 +1,72 @@
+use std::{fs, io, path::{Path, PathBuf}};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use crate::{helpers, mod_data::ModData};
+
+#[derive(Clone)]
+pub struct ModTemplate {
+    pub name: String,
+    pub category: String,
+    pub scripts: Vec<String>,
+    pub folders: Vec<String>,
+    pub source: Option<PathBuf>,
+}
+
+impl ModTemplate {
+    fn built_in(name: &str, category: &str, scripts: &[&str], folders: &[&str]) -> ModTemplate {
+        ModTemplate {
+            name: name.to_owned(),
+            category: category.to_owned(),
+            scripts: scripts.iter().map(|script| script.to_string()).collect(),
+            folders: folders.iter().map(|folder| folder.to_string()).collect(),
+            source: None,
+        }
+    }
+
+    /// Creates the template's folders in `mod_path`. User templates also copy their files,
+    /// except for the metadata, which is written from the Create Mod dialog instead.
+    pub fn scaffold(&self, mod_path: &Path) -> io::Result<()> {
+        for folder in &self.folders {
+            fs::create_dir_all(mod_path.join(folder))?;
+        }
+        if let Some(source) = &self.source {
+            let mut metadata = GlobSetBuilder::new();
+            metadata.add(Glob::new("mod.ini").unwrap());
+            metadata.add(Glob::new("mod.json").unwrap());
+            let metadata = metadata.build().unwrap_or_else(|_| GlobSet::empty());
+            helpers::copy_recursively_excluding(source, mod_path, &metadata)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn built_in_templates() -> Vec<ModTemplate> {
+    vec![
+        ModTemplate::built_in("Empty", "", &[], &[]),
+        ModTemplate::built_in("Skin", "Skin", &[], &["previews"]),
+        ModTemplate::built_in("Announcer", "Announcer", &[], &[]),
+        ModTemplate::built_in("Script mod", "Script", &["MyScriptPackage"], &[]),
+    ]
+}
+
+/// Reads user templates from the subfolders of `dir`. Each subfolder is a template named after the
+/// folder, with its category and scripts taken from its mod.ini or mod.json if present.
+pub fn load_user_templates(dir: &Path) -> io::Result<Vec<ModTemplate>> {
+    let mut templates = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue
+        }
+        let path = entry.path();
+        let metadata = ModData::from_file(ModData::metadata_file(&path)).ok();
+        templates.push(ModTemplate {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            category: metadata.as_ref().map(|data| data.category.clone()).unwrap_or_default(),
+            scripts: metadata.map(|data| data.scripts).unwrap_or_default(),
+            folders: Vec::new(),
+            source: Some(path),
+        });
+    }
+    templates.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(templates)
+}
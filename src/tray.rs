@@ -0,0 +1,54 @@
+use tray_icon::{TrayIcon, TrayIconBuilder, TrayEvent, ClickEvent, Icon, menu::{Menu, MenuItem, MenuEvent, PredefinedMenuItem}};
+
+pub enum TrayAction {
+    Show,
+    Launch,
+    Quit,
+}
+
+pub struct Tray {
+    _icon: TrayIcon,
+    show_id: u32,
+    launch_id: u32,
+    quit_id: u32,
+}
+
+impl Tray {
+    pub fn new(icon: &eframe::IconData) -> Result<Tray, String> {
+        let show = MenuItem::new("Show", true, None);
+        let launch = MenuItem::new("Launch game", true, None);
+        let quit = MenuItem::new("Quit", true, None);
+        let menu = Menu::new();
+        menu.append_items(&[&show, &launch, &PredefinedMenuItem::separator(), &quit]).map_err(|e| e.to_string())?;
+
+        let icon = Icon::from_rgba(icon.rgba.clone(), icon.width, icon.height).map_err(|e| e.to_string())?;
+        let tray_icon = TrayIconBuilder::new()
+            .with_menu(Box::new(menu))
+            .with_tooltip("GUILTY GEAR Xrd Mod Manager")
+            .with_icon(icon)
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        Ok(Tray {
+            _icon: tray_icon,
+            show_id: show.id(),
+            launch_id: launch.id(),
+            quit_id: quit.id(),
+        })
+    }
+
+    pub fn poll(&self) -> Option<TrayAction> {
+        if let Ok(event) = MenuEvent::receiver().try_recv() {
+            return match event.id {
+                id if id == self.show_id => Some(TrayAction::Show),
+                id if id == self.launch_id => Some(TrayAction::Launch),
+                id if id == self.quit_id => Some(TrayAction::Quit),
+                _ => None,
+            }
+        }
+        match TrayEvent::receiver().try_recv() {
+            Ok(event) if event.click_type == ClickEvent::Double => Some(TrayAction::Show),
+            _ => None,
+        }
+    }
+}